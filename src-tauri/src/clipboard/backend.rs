@@ -0,0 +1,269 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+// クリップボードの変更検出。
+// Linux では X11 の XFixes または Wayland の wlr-data-control（wl-paste --watch）で変更を通知する。
+// Wayland では実行時に wl-clipboard パッケージ（wl-paste コマンド）が必要
+// 通知できない環境や、監視が途中で止まった場合はポーリングで確認する
+
+/// クリップボードの変更を監視する仕組み
+pub trait ClipboardBackend: Send + Sync {
+    /// 変更を通知できる状態か。false の場合はポーリングで確認する
+    fn is_event_driven(&self) -> bool;
+}
+
+/// 変更を通知できない環境用（ポーリングのみ）
+pub struct PollingBackend;
+
+impl ClipboardBackend for PollingBackend {
+    fn is_event_driven(&self) -> bool {
+        false
+    }
+}
+
+/// 現在の環境で使える方法で監視を開始する。変更があると changed に通知する
+pub fn start(changed: Arc<Notify>) -> Box<dyn ClipboardBackend> {
+    #[cfg(target_os = "linux")]
+    {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match wayland::WaylandBackend::start(changed.clone()) {
+                Ok(backend) => return Box::new(backend),
+                Err(e) => eprintln!("Wayland clipboard watcher is unavailable: {e}"),
+            }
+        }
+        match x11::X11Backend::start(changed.clone()) {
+            Ok(backend) => return Box::new(backend),
+            Err(e) => eprintln!("X11 clipboard watcher is unavailable: {e}"),
+        }
+        eprintln!("Falling back to polling for clipboard changes");
+    }
+
+    let _ = changed;
+    Box::new(PollingBackend)
+}
+
+// 監視スレッドが止まったらポーリングに切り替えられるよう、状態を共有する
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct WatcherState {
+    active: Arc<AtomicBool>,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl WatcherState {
+    fn new() -> Self {
+        Self {
+            active: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    // 監視スレッドの終了時に呼ぶ
+    fn stopper(&self, changed: Arc<Notify>) -> impl FnOnce() {
+        let active = self.active.clone();
+        move || {
+            active.store(false, Ordering::Relaxed);
+            // 待機中の監視ループを起こしてポーリングに切り替えさせる
+            changed.notify_one();
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::{ClipboardBackend, WatcherState};
+    use std::sync::Arc;
+    use tokio::sync::Notify;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::ConnectionExt as _;
+    use x11rb::protocol::Event;
+
+    // XFixes の SelectionNotify で CLIPBOARD の所有者の変更を受け取る
+    pub struct X11Backend {
+        state: WatcherState,
+    }
+
+    impl X11Backend {
+        pub fn start(changed: Arc<Notify>) -> Result<Self, String> {
+            let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+            conn.xfixes_query_version(5, 0)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?;
+
+            let root = conn.setup().roots[screen_num].root;
+            let clipboard = conn
+                .intern_atom(false, b"CLIPBOARD")
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?
+                .atom;
+            conn.xfixes_select_selection_input(
+                root,
+                clipboard,
+                SelectionEventMask::SET_SELECTION_OWNER
+                    | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                    | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+            )
+            .map_err(|e| e.to_string())?;
+            conn.flush().map_err(|e| e.to_string())?;
+
+            let state = WatcherState::new();
+            let stop = state.stopper(changed.clone());
+            std::thread::spawn(move || {
+                loop {
+                    match conn.wait_for_event() {
+                        Ok(Event::XfixesSelectionNotify(_)) => changed.notify_one(),
+                        Ok(_) => {}
+                        Err(e) => {
+                            eprintln!("X11 clipboard watcher stopped: {e}");
+                            break;
+                        }
+                    }
+                }
+                stop();
+            });
+
+            Ok(Self { state })
+        }
+    }
+
+    impl ClipboardBackend for X11Backend {
+        fn is_event_driven(&self) -> bool {
+            self.state.is_active()
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod wayland {
+    use super::{ClipboardBackend, WatcherState};
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use std::sync::Arc;
+    use tokio::sync::Notify;
+
+    // wl-clipboard の `wl-paste --watch` を使う。wl-paste は wlr-data-control プロトコルで
+    // クリップボードを監視し、変更のたびにコマンド（ここでは echo）を実行する
+    pub struct WaylandBackend {
+        state: WatcherState,
+    }
+
+    impl WaylandBackend {
+        pub fn start(changed: Arc<Notify>) -> Result<Self, String> {
+            let mut command = Command::new("wl-paste");
+            command
+                .args(["--watch", "echo"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null());
+            // SAFETY: fork 後に async-signal-safe な prctl を呼ぶだけ。
+            // 起動したスレッドが終了したら wl-paste も終了させる（アプリ終了後に残らないように）
+            unsafe {
+                command.pre_exec(|| {
+                    libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+                    Ok(())
+                });
+            }
+
+            // PR_SET_PDEATHSIG は起動したスレッドの終了で発火するため、監視スレッドから起動する
+            let (started_tx, started_rx) = std::sync::mpsc::channel();
+            let state = WatcherState::new();
+            let stop = state.stopper(changed.clone());
+            std::thread::spawn(move || {
+                let mut child = match command.spawn() {
+                    Ok(child) => {
+                        let _ = started_tx.send(Ok(()));
+                        child
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        let _ = started_tx.send(Err(
+                            "wl-paste was not found; install wl-clipboard to detect changes on Wayland"
+                                .to_string(),
+                        ));
+                        return;
+                    }
+                    Err(e) => {
+                        let _ = started_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+
+                if let Some(stdout) = child.stdout.take() {
+                    for _ in BufReader::new(stdout).lines() {
+                        changed.notify_one();
+                    }
+                }
+
+                // 対応していないコンポジタでは wl-paste がすぐに終了する
+                let status = child.wait();
+                eprintln!("Wayland clipboard watcher stopped: {status:?}");
+                stop();
+            });
+
+            started_rx
+                .recv()
+                .map_err(|e| e.to_string())
+                .and_then(|started| started)?;
+            Ok(Self { state })
+        }
+    }
+
+    impl ClipboardBackend for WaylandBackend {
+        fn is_event_driven(&self) -> bool {
+            self.state.is_active()
+        }
+    }
+}
+
+/// ポーリング間隔。変更があると短くし、変更がない間は徐々に長くする
+pub struct AdaptiveInterval {
+    current: Duration,
+}
+
+impl Default for AdaptiveInterval {
+    fn default() -> Self {
+        Self { current: Self::MIN }
+    }
+}
+
+impl AdaptiveInterval {
+    const MIN: Duration = Duration::from_millis(250);
+    const MAX: Duration = Duration::from_secs(2);
+
+    /// 確認した結果を受けて次の待ち時間を返す
+    pub fn next(&mut self, changed: bool) -> Duration {
+        self.current = if changed {
+            Self::MIN
+        } else {
+            (self.current * 3 / 2).min(Self::MAX)
+        };
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_interval() {
+        let mut interval = AdaptiveInterval::default();
+        let first = interval.next(false);
+        assert!(first > AdaptiveInterval::MIN);
+
+        let mut last = first;
+        for _ in 0..20 {
+            last = interval.next(false);
+        }
+        assert_eq!(last, AdaptiveInterval::MAX);
+
+        assert_eq!(interval.next(true), AdaptiveInterval::MIN);
+    }
+}
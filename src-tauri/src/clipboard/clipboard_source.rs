@@ -0,0 +1,216 @@
+use crate::models::{ClipboardFormat, FormatData, Selection};
+use arboard::{Clipboard, Get, ImageData, Set};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+// システムのクリップボードへの読み書き。
+// 監視処理をクリップボードの実装から切り離し、テストではメモリ上の実装に差し替える
+
+/// クリップボードの内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipboardContent {
+    // プレーンテキストと、同時に提供されている書式付きの表現（HTML など）
+    Text {
+        text: String,
+        formats: Vec<FormatData>,
+    },
+    Image(ClipboardImage),
+    Files(Vec<PathBuf>),
+}
+
+/// RGBA 形式の画像
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardImage {
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// クリップボードの読み書き
+pub trait ClipboardSource: Send {
+    /// 現在の内容を読み取る。空の場合や対応していない形式の場合は Err を返す
+    fn read(&mut self) -> Result<ClipboardContent>;
+
+    /// プレーンテキストのみを読み取る。テキストがない場合は Err を返す
+    fn read_text(&mut self) -> Result<String> {
+        match self.read()? {
+            ClipboardContent::Text { text, .. } => Ok(text),
+            _ => Err("clipboard does not contain text".into()),
+        }
+    }
+
+    /// 内容を書き込む。テキストの書式は HTML のみ書き込む
+    fn write(&mut self, content: &ClipboardContent) -> Result<()>;
+
+    /// 内容が変わるたびに増える値。取得できない実装は None を返す（内容を読み取って比較する）
+    fn change_count(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// arboard を使ったシステムのクリップボード
+pub struct ArboardSource {
+    clipboard: Clipboard,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    selection: Selection,
+}
+
+impl ArboardSource {
+    /// 指定したセレクションを読み書きする。PRIMARY は Linux のみ
+    pub fn new(selection: Selection) -> Result<Self> {
+        if cfg!(not(target_os = "linux")) && selection == Selection::Primary {
+            return Err("PRIMARY selection is only available on Linux".into());
+        }
+        Ok(Self {
+            clipboard: Clipboard::new()?,
+            selection,
+        })
+    }
+
+    fn get(&mut self) -> Get<'_> {
+        #[cfg(target_os = "linux")]
+        {
+            use arboard::GetExtLinux;
+            let kind = linux_kind(self.selection);
+            self.clipboard.get().clipboard(kind)
+        }
+        #[cfg(not(target_os = "linux"))]
+        self.clipboard.get()
+    }
+
+    fn set(&mut self) -> Set<'_> {
+        #[cfg(target_os = "linux")]
+        {
+            use arboard::SetExtLinux;
+            let kind = linux_kind(self.selection);
+            self.clipboard.set().clipboard(kind)
+        }
+        #[cfg(not(target_os = "linux"))]
+        self.clipboard.set()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_kind(selection: Selection) -> arboard::LinuxClipboardKind {
+    match selection {
+        Selection::Clipboard => arboard::LinuxClipboardKind::Clipboard,
+        Selection::Primary => arboard::LinuxClipboardKind::Primary,
+    }
+}
+
+impl ClipboardSource for ArboardSource {
+    fn read(&mut self) -> Result<ClipboardContent> {
+        // ファイルマネージャーでのコピーはテキストとしても読めるため、ファイル一覧を優先する
+        if let Ok(paths) = self.get().file_list() {
+            if !paths.is_empty() {
+                return Ok(ClipboardContent::Files(paths));
+            }
+        }
+
+        // テキストがない場合は画像を読み取る
+        match self.get().text() {
+            Ok(text) => {
+                // ブラウザやオフィスソフトからのコピーは書式付きの表現も保存する
+                // （arboard は RTF の読み取りに対応していないため、現状は HTML のみ）
+                let formats = self
+                    .get()
+                    .html()
+                    .ok()
+                    .filter(|html| !html.trim().is_empty())
+                    .map(|html| FormatData {
+                        format: ClipboardFormat::Html,
+                        data: html,
+                    })
+                    .into_iter()
+                    .collect();
+                Ok(ClipboardContent::Text { text, formats })
+            }
+            Err(_) => {
+                let image = self.get().image()?;
+                Ok(ClipboardContent::Image(ClipboardImage {
+                    width: image.width as u32,
+                    height: image.height as u32,
+                    bytes: image.bytes.into_owned(),
+                }))
+            }
+        }
+    }
+
+    // ファイル一覧や HTML、画像は要求しない（PRIMARY の定期的な確認用）
+    fn read_text(&mut self) -> Result<String> {
+        Ok(self.get().text()?)
+    }
+
+    fn write(&mut self, content: &ClipboardContent) -> Result<()> {
+        match content {
+            ClipboardContent::Text { text, formats } => {
+                let html = formats.iter().find(|f| f.format == ClipboardFormat::Html);
+                match html {
+                    Some(html) => self.set().html(&html.data, Some(text))?,
+                    None => self.set().text(text)?,
+                }
+            }
+            ClipboardContent::Image(image) => self.set().image(ImageData {
+                width: image.width as usize,
+                height: image.height as usize,
+                bytes: Cow::Borrowed(&image.bytes),
+            })?,
+            ClipboardContent::Files(paths) => self.set().file_list(paths)?,
+        }
+        Ok(())
+    }
+}
+
+/// メモリ上のクリップボード（テスト用）。
+/// clone したハンドルで他のアプリケーションからのコピーを再現できる
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MemoryClipboard {
+    state: std::sync::Arc<std::sync::Mutex<MemoryState>>,
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct MemoryState {
+    content: Option<ClipboardContent>,
+    change_count: u64,
+}
+
+#[cfg(test)]
+impl MemoryClipboard {
+    /// 他のアプリケーションがコピーした状態にする
+    pub fn copy(&self, content: ClipboardContent) {
+        let mut state = self.state.lock().unwrap();
+        state.content = Some(content);
+        state.change_count += 1;
+    }
+
+    pub fn copy_text(&self, text: &str) {
+        self.copy(ClipboardContent::Text {
+            text: text.to_string(),
+            formats: Vec::new(),
+        });
+    }
+
+    pub fn content(&self) -> Option<ClipboardContent> {
+        self.state.lock().unwrap().content.clone()
+    }
+}
+
+#[cfg(test)]
+impl ClipboardSource for MemoryClipboard {
+    fn read(&mut self) -> Result<ClipboardContent> {
+        Ok(self.content().ok_or("clipboard is empty")?)
+    }
+
+    fn write(&mut self, content: &ClipboardContent) -> Result<()> {
+        self.copy(content.clone());
+        Ok(())
+    }
+
+    fn change_count(&self) -> Option<u64> {
+        Some(self.state.lock().unwrap().change_count)
+    }
+}
@@ -0,0 +1,80 @@
+use super::source::SourceApp;
+use regex::Regex;
+
+/// パスワードマネージャーなどが「履歴に残さないこと」を示すために付けるクリップボードの形式
+pub const CONCEALED_TYPES: &[&str] = &[
+    "x-kde-passwordManagerHint",
+    "org.nspasteboard.ConcealedType",
+    "org.nspasteboard.TransientType",
+    "ExcludeClipboardContentFromMonitorProcessing",
+];
+
+/// 記録しないアプリケーションの条件
+#[derive(Debug, Default)]
+pub struct ExclusionRules {
+    // アプリケーション名（WM_CLASS など）。大文字小文字は区別しない
+    apps: Vec<String>,
+    // ウィンドウタイトルの正規表現
+    titles: Vec<Regex>,
+}
+
+impl ExclusionRules {
+    pub fn new(apps: &[String], titles: &[String]) -> Result<Self, String> {
+        let titles = titles
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| format!("invalid window title pattern {pattern:?}: {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            apps: apps
+                .iter()
+                .map(|app| app.trim().to_lowercase())
+                .filter(|app| !app.is_empty())
+                .collect(),
+            titles,
+        })
+    }
+
+    pub fn excludes(&self, app: &SourceApp) -> bool {
+        let name = app.name.to_lowercase();
+        if self.apps.contains(&name) {
+            return true;
+        }
+
+        app.title
+            .as_deref()
+            .is_some_and(|title| self.titles.iter().any(|regex| regex.is_match(title)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, title: Option<&str>) -> SourceApp {
+        SourceApp {
+            name: name.to_string(),
+            pid: None,
+            title: title.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_excludes_by_name_and_title() {
+        let rules = ExclusionRules::new(
+            &["KeePassXC".to_string()],
+            &["(?i)online banking".to_string()],
+        )
+        .unwrap();
+
+        assert!(rules.excludes(&app("keepassxc", None)));
+        assert!(rules.excludes(&app("firefox", Some("My Online Banking - Firefox"))));
+        assert!(!rules.excludes(&app("firefox", Some("Search results"))));
+        assert!(!rules.excludes(&app("Gnome-terminal", None)));
+
+        assert!(ExclusionRules::new(&[], &["(".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::models::FileEntry;
+use std::path::Path;
+
+/// コピー時点でのファイルの存在・サイズ・MIME タイプを調べる
+pub fn inspect(path: &Path) -> FileEntry {
+    let metadata = std::fs::metadata(path).ok();
+    let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+
+    let mime_type = if is_dir {
+        Some("inode/directory".to_string())
+    } else {
+        mime_guess::from_path(path)
+            .first()
+            .map(|mime| mime.essence_str().to_string())
+    };
+
+    FileEntry {
+        path: path.to_string_lossy().into_owned(),
+        exists: metadata.is_some(),
+        is_dir,
+        size: metadata
+            .as_ref()
+            .filter(|m| m.is_file())
+            .map(|m| m.len() as i64),
+        mime_type,
+    }
+}
+
+/// ファイルアイテムの content（検索・表示用に改行区切りのパス）
+pub fn content(files: &[FileEntry]) -> String {
+    files
+        .iter()
+        .map(|f| f.path.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect() {
+        let dir = std::env::temp_dir().join(format!("clipedia-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("note.txt");
+        std::fs::write(&file, "hello").unwrap();
+
+        let entry = inspect(&file);
+        assert!(entry.exists);
+        assert!(!entry.is_dir);
+        assert_eq!(entry.size, Some(5));
+        assert_eq!(entry.mime_type.as_deref(), Some("text/plain"));
+
+        let entry = inspect(&dir);
+        assert!(entry.exists && entry.is_dir);
+        assert_eq!(entry.size, None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let entry = inspect(&file);
+        assert!(!entry.exists);
+        assert_eq!(entry.size, None);
+    }
+}
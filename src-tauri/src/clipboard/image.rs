@@ -0,0 +1,95 @@
+use crate::models::{EncodedImage, ImageInfo};
+use image::{imageops, ImageFormat, RgbaImage};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+// サムネイルの最大辺（ピクセル）
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// RGBA の画像データから重複検出用のハッシュを計算する
+pub fn hash_rgba(width: u32, height: u32, rgba: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(width.to_le_bytes());
+    hasher.update(height.to_le_bytes());
+    hasher.update(rgba);
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// RGBA の画像データを PNG とサムネイルにエンコードする
+pub fn encode(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Result<EncodedImage, Box<dyn std::error::Error>> {
+    let hash = hash_rgba(width, height, rgba);
+    let image = RgbaImage::from_raw(width, height, rgba.to_vec())
+        .ok_or("image data does not match its dimensions")?;
+
+    let png = to_png(&image)?;
+
+    let thumbnail = if width > THUMBNAIL_SIZE || height > THUMBNAIL_SIZE {
+        let scale = THUMBNAIL_SIZE as f64 / width.max(height) as f64;
+        let thumb_width = ((width as f64 * scale).round() as u32).max(1);
+        let thumb_height = ((height as f64 * scale).round() as u32).max(1);
+        imageops::thumbnail(&image, thumb_width, thumb_height)
+    } else {
+        image
+    };
+    let thumbnail_png = to_png(&thumbnail)?;
+
+    Ok(EncodedImage {
+        info: ImageInfo {
+            width,
+            height,
+            byte_size: png.len() as i64,
+        },
+        hash,
+        png,
+        thumbnail_png,
+    })
+}
+
+/// PNG をデコードして (幅, 高さ, RGBA) を返す
+pub fn decode(png: &[u8]) -> Result<(u32, u32, Vec<u8>), Box<dyn std::error::Error>> {
+    let image = image::load_from_memory_with_format(png, ImageFormat::Png)?.into_rgba8();
+    Ok((image.width(), image.height(), image.into_raw()))
+}
+
+fn to_png(image: &RgbaImage) -> Result<Vec<u8>, image::ImageError> {
+    let mut buffer = Cursor::new(Vec::new());
+    image.write_to(&mut buffer, ImageFormat::Png)?;
+    Ok(buffer.into_inner())
+}
+
+/// 画像アイテムの表示用テキスト
+pub fn label(width: u32, height: u32) -> String {
+    format!("画像 ({width}×{height})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let (width, height) = (600, 300);
+        let rgba: Vec<u8> = (0..width * height * 4).map(|i| (i % 251) as u8).collect();
+
+        let encoded = encode(width, height, &rgba).unwrap();
+        assert_eq!(encoded.info.width, width);
+        assert_eq!(encoded.info.height, height);
+        assert_eq!(encoded.info.byte_size, encoded.png.len() as i64);
+        assert_eq!(encoded.hash, hash_rgba(width, height, &rgba));
+
+        let (w, h, decoded) = decode(&encoded.png).unwrap();
+        assert_eq!((w, h), (width, height));
+        assert_eq!(decoded, rgba);
+
+        let (tw, th, _) = decode(&encoded.thumbnail_png).unwrap();
+        assert_eq!((tw, th), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+    }
+}
@@ -1,3 +1,13 @@
+pub mod backend;
+pub mod clipboard_source;
+pub mod exclusion;
+pub mod files;
+pub mod image;
 pub mod monitor;
+pub mod primary;
+pub mod source;
 
 pub use monitor::ClipboardMonitor;
+
+#[cfg(test)]
+mod tests;
@@ -1,87 +1,487 @@
+use super::backend::{self, AdaptiveInterval};
+use super::clipboard_source::{ArboardSource, ClipboardContent, ClipboardImage, ClipboardSource};
+use super::exclusion::ExclusionRules;
+use super::primary::{self, Debouncer, PrimarySelection};
+use super::source::{self, SourceApp, SourceDetector};
+use super::{files, image};
 use crate::content_analyzer::ContentAnalyzer;
-use crate::db::Database;
-use crate::models::{ClipboardItem, ClipboardItemType};
-use arboard::Clipboard;
+use crate::db::{self, Database, InsertOutcome};
+use crate::error::{ClipediaError, Result};
+use crate::events::HistoryEvents;
+use crate::models::{ClipboardFormat, ClipboardItem, ClipboardItemType, FormatData, Selection};
+use crate::settings::RestoreTarget;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 use tokio::time::sleep;
 
+// 変更を通知できる環境でも、通知の取りこぼしに備えてこの間隔で確認する
+const EVENT_DRIVEN_FALLBACK_INTERVAL: Duration = Duration::from_secs(30);
+
+// 直前に読み取った内容（変更検出用）。画像はハッシュで比較する
+#[derive(PartialEq)]
+enum LastContent {
+    Text(String),
+    Image(String),
+    Files(Vec<PathBuf>),
+}
+
+impl LastContent {
+    fn of(content: &ClipboardContent) -> Self {
+        match content {
+            ClipboardContent::Text { text, .. } => LastContent::Text(text.clone()),
+            ClipboardContent::Image(data) => {
+                LastContent::Image(image::hash_rgba(data.width, data.height, &data.bytes))
+            }
+            ClipboardContent::Files(paths) => LastContent::Files(paths.clone()),
+        }
+    }
+
+    // データベースの content_hash と同じ方法で計算したハッシュ
+    fn content_hash(&self) -> String {
+        match self {
+            LastContent::Text(text) => db::content_hash(ClipboardItemType::Text.as_str(), text),
+            LastContent::Image(hash) => hash.clone(),
+            LastContent::Files(paths) => {
+                let content = paths
+                    .iter()
+                    .map(|path| path.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                db::content_hash(ClipboardItemType::File.as_str(), &content)
+            }
+        }
+    }
+}
+
 pub struct ClipboardMonitor {
-    clipboard: Arc<Mutex<Clipboard>>,
+    clipboard: Mutex<Box<dyn ClipboardSource>>,
+    // 直前に確認したときの ClipboardSource::change_count
+    last_change_count: Mutex<Option<u64>>,
     db: Arc<Mutex<Database>>,
-    last_content: Arc<Mutex<Option<String>>>,
+    last_content: Arc<Mutex<Option<LastContent>>>,
     is_monitoring: Arc<Mutex<bool>>,
+    // アイテムを保存したときに通知する（保存上限のチェック用）
+    inserted: Arc<Notify>,
+    // 履歴の変更をフロントエンドとトレイに通知する
+    events: HistoryEvents,
+    source_detector: Box<dyn SourceDetector>,
+    // 記録しないアプリケーション
+    exclusions: Mutex<ExclusionRules>,
+    // Linux の PRIMARY セレクション。記録しない設定の場合も、アイテムの書き込みには使う
+    primary: Option<Mutex<Box<dyn ClipboardSource>>>,
+    primary_selection: Mutex<PrimarySelection>,
+    primary_debouncer: Mutex<Debouncer>,
 }
 
 impl ClipboardMonitor {
-    pub fn new(db: Arc<Mutex<Database>>) -> Result<Self, Box<dyn std::error::Error>> {
-        let clipboard = Clipboard::new()?;
-        Ok(Self {
-            clipboard: Arc::new(Mutex::new(clipboard)),
+    pub fn new(
+        db: Arc<Mutex<Database>>,
+        inserted: Arc<Notify>,
+        events: HistoryEvents,
+    ) -> Result<Self> {
+        let clipboard =
+            ArboardSource::new(Selection::Clipboard).map_err(ClipediaError::clipboard)?;
+        let monitor = Self::with_source(
+            db,
+            inserted,
+            events,
+            Box::new(clipboard),
+            source::detector(),
+        );
+        Ok(match ArboardSource::new(Selection::Primary) {
+            Ok(primary) => monitor.with_primary(Box::new(primary)),
+            Err(_) => monitor,
+        })
+    }
+
+    /// クリップボードとコピー元の判別方法を指定して作成する
+    pub fn with_source(
+        db: Arc<Mutex<Database>>,
+        inserted: Arc<Notify>,
+        events: HistoryEvents,
+        clipboard: Box<dyn ClipboardSource>,
+        source_detector: Box<dyn SourceDetector>,
+    ) -> Self {
+        Self {
+            clipboard: Mutex::new(clipboard),
+            last_change_count: Mutex::new(None),
             db,
             last_content: Arc::new(Mutex::new(None)),
             is_monitoring: Arc::new(Mutex::new(true)),
-        })
+            inserted,
+            events,
+            source_detector,
+            exclusions: Mutex::new(ExclusionRules::default()),
+            primary: None,
+            primary_selection: Mutex::new(PrimarySelection::default()),
+            primary_debouncer: Mutex::new(Debouncer::default()),
+        }
     }
 
-    pub async fn start_monitoring(&self) {
+    /// PRIMARY セレクションを読み書きできるようにする
+    pub fn with_primary(mut self, primary: Box<dyn ClipboardSource>) -> Self {
+        self.primary = Some(Mutex::new(primary));
+        self
+    }
+
+    pub async fn start_monitoring(self: Arc<Self>) {
+        let changed = Arc::new(Notify::new());
+        let backend = backend::start(changed.clone());
+
+        let mut interval = AdaptiveInterval::default();
         loop {
-            // 監視が有効な場合のみ処理
-            let is_monitoring = *self.is_monitoring.lock().unwrap();
-            if is_monitoring {
-                if let Ok(current_content) = self.get_clipboard_content() {
-                    let should_save = {
-                        let mut last_content = self.last_content.lock().unwrap();
-                        if last_content.as_ref() != Some(&current_content) {
-                            *last_content = Some(current_content.clone());
-                            true
-                        } else {
-                            false
-                        }
-                    };
-
-                    if should_save && !current_content.trim().is_empty() {
-                        let mut item =
-                            ClipboardItem::new(current_content.clone(), ClipboardItemType::Text);
-
-                        // コンテンツ分析でタグを自動付与
-                        let auto_tags = ContentAnalyzer::analyze(&current_content);
-                        item.tags.extend(auto_tags);
-
-                        if let Ok(db) = self.db.lock() {
-                            let _ = db.insert_item(&item);
-                        }
-                    }
-                }
+            // クリップボードの読み取りやコピー元の確認は X サーバーの応答を待つため、
+            // 非同期タスクのスレッドを止めないよう別スレッドで実行する
+            let monitor = self.clone();
+            let captured = tokio::task::spawn_blocking(move || monitor.poll())
+                .await
+                .unwrap_or(false);
+
+            // 変更の通知を待つ。通知できない環境ではポーリング間隔を調整しながら確認する
+            let mut wait = if backend.is_event_driven() {
+                EVENT_DRIVEN_FALLBACK_INTERVAL
+            } else {
+                interval.next(captured)
+            };
+            if self.captures_primary() {
+                wait = wait.min(primary::POLL_INTERVAL);
+            }
+            tokio::select! {
+                _ = changed.notified() => {}
+                _ = sleep(wait) => {}
             }
+        }
+    }
 
-            sleep(Duration::from_millis(500)).await;
+    /// クリップボード（と設定されていれば PRIMARY セレクション）を一度確認し、
+    /// 直前と異なる内容であれば保存する。内容が変わった場合は true を返す。
+    /// 監視を停止している間にコピーされた内容は、再開後も保存しない
+    pub fn poll(&self) -> bool {
+        let clipboard_changed = self.poll_clipboard();
+        let primary_changed = self.poll_primary();
+        clipboard_changed || primary_changed
+    }
+
+    fn poll_clipboard(&self) -> bool {
+        // 変更回数を取得できる場合は、変わっていなければ読み取らない
+        let change_count = self.clipboard.lock().unwrap().change_count();
+        {
+            let mut last_change_count = self.last_change_count.lock().unwrap();
+            if change_count.is_some() && *last_change_count == change_count {
+                return false;
+            }
+            *last_change_count = change_count;
+        }
+
+        let Ok(content) = self.clipboard.lock().unwrap().read() else {
+            return false;
+        };
+        if !self.is_monitoring() {
+            self.update_last_content(LastContent::of(&content));
+            return false;
+        }
+
+        match content {
+            ClipboardContent::Text { text, formats } => self.handle_text(text, formats),
+            ClipboardContent::Image(image) => self.handle_image(image),
+            ClipboardContent::Files(paths) => self.handle_files(paths),
         }
     }
 
-    fn get_clipboard_content(&self) -> Result<String, Box<dyn std::error::Error>> {
-        let mut clipboard = self.clipboard.lock().unwrap();
-        match clipboard.get_text() {
-            Ok(text) => Ok(text),
-            Err(e) => Err(Box::new(e)),
+    fn captures_primary(&self) -> bool {
+        self.primary.is_some() && self.primary_selection.lock().unwrap().capture
+    }
+
+    // PRIMARY セレクションのテキストを確認し、選択が落ち着いたら保存する
+    fn poll_primary(&self) -> bool {
+        let Some(primary) = &self.primary else {
+            return false;
+        };
+        let options = *self.primary_selection.lock().unwrap();
+        if !options.capture {
+            return false;
+        }
+        let Ok(text) = primary.lock().unwrap().read_text() else {
+            return false;
+        };
+
+        let stable = {
+            let mut debouncer = self.primary_debouncer.lock().unwrap();
+            if !self.is_monitoring() {
+                debouncer.mark_seen(&text);
+                return false;
+            }
+            debouncer.observe(text, Instant::now(), options.debounce)
+        };
+        match stable {
+            Some(text) => {
+                self.save_text(text, Vec::new(), Selection::Primary);
+                true
+            }
+            None => false,
         }
     }
 
-    pub fn copy_to_clipboard(&self, content: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut clipboard = self.clipboard.lock().unwrap();
-        clipboard.set_text(content)?;
+    pub fn set_primary_selection(&self, options: PrimarySelection) {
+        *self.primary_selection.lock().unwrap() = options;
+    }
 
-        // Update last content to avoid re-saving
+    // 直前の内容と異なる場合のみ true を返し、直前の内容を更新する
+    fn update_last_content(&self, current: LastContent) -> bool {
         let mut last_content = self.last_content.lock().unwrap();
-        *last_content = Some(content.to_string());
+        if last_content.as_ref() != Some(&current) {
+            *last_content = Some(current);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn set_exclusions(&self, exclusions: ExclusionRules) {
+        *self.exclusions.lock().unwrap() = exclusions;
+    }
+
+    // コピー元のアプリケーションを調べる（Clipedia 自身がコピーした場合は None）。
+    // 除外対象のアプリケーションや、履歴に残さない印の付いた内容の場合は Err を返す
+    fn check_source(&self, selection: Selection) -> Result<Option<SourceApp>, ()> {
+        if self.source_detector.is_concealed(selection) {
+            return Err(());
+        }
+        let owner = match selection {
+            Selection::Clipboard => self.source_detector.clipboard_owner(),
+            Selection::Primary => self.source_detector.primary_owner(),
+        };
+
+        let source = owner.filter(|app| app.pid != Some(std::process::id()));
+        if let Some(app) = &source {
+            if self.exclusions.lock().unwrap().excludes(app) {
+                return Err(());
+            }
+        }
+        Ok(source)
+    }
+
+    fn handle_text(&self, current_content: String, formats: Vec<FormatData>) -> bool {
+        let should_save = self.update_last_content(LastContent::Text(current_content.clone()));
+        if should_save {
+            self.save_text(current_content, formats, Selection::Clipboard);
+        }
+        should_save
+    }
+
+    // 空白のみのテキストは保存しない
+    fn save_text(&self, content: String, formats: Vec<FormatData>, selection: Selection) {
+        if content.trim().is_empty() {
+            return;
+        }
+        let Ok(source) = self.check_source(selection) else {
+            return;
+        };
+
+        let mut item = ClipboardItem::new(content, ClipboardItemType::Text);
+        item.application_source = source.map(|app| app.name);
+        item.selection = selection;
+
+        // コンテンツ分析でタグを自動付与
+        let auto_tags = ContentAnalyzer::analyze(&item.content);
+        item.tags.extend(auto_tags);
+
+        if let Ok(db) = self.db.lock() {
+            if let Ok(outcome) = db.insert_text_item(&item, &formats) {
+                self.saved(&db, &outcome, &item.id);
+            }
+        }
+    }
+
+    fn handle_image(&self, data: ClipboardImage) -> bool {
+        let (width, height) = (data.width, data.height);
+        let hash = image::hash_rgba(width, height, &data.bytes);
+        if !self.update_last_content(LastContent::Image(hash.clone())) {
+            return false;
+        }
+        let Ok(source) = self.check_source(Selection::Clipboard) else {
+            return true;
+        };
+
+        // 同じ画像が保存済みの場合はエンコードせずにコピー回数を増やすだけにする
+        if let Ok(db) = self.db.lock() {
+            if let Ok(Some(id)) = db.record_copy(&hash) {
+                self.events.item_updated(&db, &id);
+                return true;
+            }
+        }
+
+        let encoded = match image::encode(width, height, &data.bytes) {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                eprintln!("Failed to encode clipboard image: {e}");
+                return true;
+            }
+        };
+
+        let mut item = ClipboardItem::new(image::label(width, height), ClipboardItemType::Image);
+        item.image = Some(encoded.info.clone());
+        item.application_source = source.map(|app| app.name);
+
+        if let Ok(db) = self.db.lock() {
+            if let Ok(outcome) = db.insert_image_item(&item, &encoded) {
+                self.saved(&db, &outcome, &item.id);
+            }
+        }
+        true
+    }
+
+    fn handle_files(&self, paths: Vec<PathBuf>) -> bool {
+        if !self.update_last_content(LastContent::Files(paths.clone())) {
+            return false;
+        }
+        let Ok(source) = self.check_source(Selection::Clipboard) else {
+            return true;
+        };
+
+        let entries: Vec<_> = paths.iter().map(|path| files::inspect(path)).collect();
+        let mut item = ClipboardItem::new(files::content(&entries), ClipboardItemType::File);
+        item.files = Some(entries.clone());
+        item.application_source = source.map(|app| app.name);
+
+        if let Ok(db) = self.db.lock() {
+            if let Ok(outcome) = db.insert_file_item(&item, &entries) {
+                self.saved(&db, &outcome, &item.id);
+            }
+        }
+        true
+    }
+
+    // アイテムを保存したことを通知する。新しく追加された場合のみ保存上限をチェックする
+    fn saved(&self, db: &Database, outcome: &InsertOutcome, inserted_id: &str) {
+        if *outcome == InsertOutcome::Inserted {
+            self.inserted.notify_one();
+        }
+        self.events.inserted(db, outcome, inserted_id);
+    }
+
+    /// 現在のクリップボードのテキスト（スニペットの {{clipboard}} の展開用）
+    pub fn clipboard_text(&self) -> Option<String> {
+        self.clipboard.lock().unwrap().read_text().ok()
+    }
+
+    pub fn copy_to_clipboard(&self, content: &str) -> Result<()> {
+        let target = self.primary_selection.lock().unwrap().restore_target;
+        self.write(
+            ClipboardContent::Text {
+                text: content.to_string(),
+                formats: Vec::new(),
+            },
+            target,
+        )
+    }
+
+    // 書き込み先に書き込み、自分で書き込んだ内容を再度保存しないよう直前の内容として記録する。
+    // PRIMARY を使えない環境では CLIPBOARD にのみ書き込む
+    fn write(&self, content: ClipboardContent, target: RestoreTarget) -> Result<()> {
+        let (to_clipboard, to_primary) = match (target, &self.primary) {
+            (RestoreTarget::Primary, Some(_)) => (false, true),
+            (RestoreTarget::Both, Some(_)) => (true, true),
+            _ => (true, false),
+        };
+
+        if to_clipboard {
+            let mut clipboard = self.clipboard.lock().unwrap();
+            clipboard
+                .write(&content)
+                .map_err(ClipediaError::clipboard)?;
+            *self.last_change_count.lock().unwrap() = clipboard.change_count();
+        }
+        if let (true, Some(primary)) = (to_primary, &self.primary) {
+            primary
+                .lock()
+                .unwrap()
+                .write(&content)
+                .map_err(ClipediaError::clipboard)?;
+            if let ClipboardContent::Text { text, .. } = &content {
+                self.primary_debouncer.lock().unwrap().mark_seen(text);
+            }
+        }
+
+        // Update last content to avoid re-saving
+        let current = LastContent::of(&content);
+        if let Ok(db) = self.db.lock() {
+            // 履歴からのコピーも使用回数に含める
+            if let Ok(Some(id)) = db.record_copy(&current.content_hash()) {
+                self.events.item_updated(&db, &id);
+            }
+        }
+        if to_clipboard {
+            *self.last_content.lock().unwrap() = Some(current);
+        }
 
         Ok(())
     }
 
-    pub fn toggle_monitoring(&self) -> bool {
-        let mut is_monitoring = self.is_monitoring.lock().unwrap();
-        *is_monitoring = !*is_monitoring;
-        *is_monitoring
+    /// 履歴のアイテムを設定された書き込み先（RestoreTarget）にコピーする
+    /// （画像やファイル、書式付きテキストも復元する）
+    /// plain_text が true の場合、テキストアイテムは書式なしでコピーする
+    /// データベースのロックを取得するため、呼び出し側はロックを保持していないこと
+    pub fn copy_item_to_clipboard(&self, id: &str, plain_text: bool) -> Result<()> {
+        let target = self.primary_selection.lock().unwrap().restore_target;
+        self.copy_item_to(id, plain_text, target)
+    }
+
+    /// 履歴のアイテムを CLIPBOARD、PRIMARY またはその両方にコピーする
+    pub fn copy_item_to(&self, id: &str, plain_text: bool, target: RestoreTarget) -> Result<()> {
+        let content = self.item_content(id, plain_text)?;
+        self.write(content, target)
+    }
+
+    // 保存されているアイテムからクリップボードに書き込む内容を作る
+    fn item_content(&self, id: &str, plain_text: bool) -> Result<ClipboardContent> {
+        let (item, png, formats) = {
+            let db = self.db.lock()?;
+            let item = db
+                .get_item(id)?
+                .ok_or_else(|| ClipediaError::not_found("item", id))?;
+            let png = match item.item_type {
+                ClipboardItemType::Image => db.get_image_data(id)?,
+                _ => None,
+            };
+            let formats = match item.item_type {
+                ClipboardItemType::Text if !plain_text => db.get_formats(id)?,
+                _ => Vec::new(),
+            };
+            (item, png, formats)
+        };
+
+        if let Some(entries) = &item.files {
+            let paths = entries.iter().map(|f| PathBuf::from(&f.path)).collect();
+            return Ok(ClipboardContent::Files(paths));
+        }
+
+        if let Some(png) = png {
+            let (width, height, rgba) =
+                image::decode(&png).map_err(|e| ClipediaError::Internal(e.to_string()))?;
+            return Ok(ClipboardContent::Image(ClipboardImage {
+                width,
+                height,
+                bytes: rgba,
+            }));
+        }
+
+        // HTML とプレーンテキストを同時に設定する
+        let formats = formats
+            .into_iter()
+            .filter(|f| f.format == ClipboardFormat::Html)
+            .take(1)
+            .collect();
+        Ok(ClipboardContent::Text {
+            text: item.content,
+            formats,
+        })
+    }
+
+    pub fn set_monitoring(&self, enabled: bool) {
+        *self.is_monitoring.lock().unwrap() = enabled;
     }
 
     pub fn is_monitoring(&self) -> bool {
@@ -0,0 +1,99 @@
+use crate::settings::RestoreTarget;
+use std::time::{Duration, Instant};
+
+// Linux の PRIMARY セレクション（マウスで選択しただけのテキスト）の記録。
+// 選択範囲はドラッグ中にも変化するため、一定時間変化しなかった選択だけを記録する
+
+// PRIMARY を記録する場合の確認間隔。PRIMARY の変更は通知を受け取らないため常にポーリングする
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// PRIMARY セレクションの設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimarySelection {
+    pub capture: bool,
+    pub debounce: Duration,
+    pub restore_target: RestoreTarget,
+}
+
+impl Default for PrimarySelection {
+    fn default() -> Self {
+        Self {
+            capture: false,
+            debounce: Duration::from_millis(500),
+            restore_target: RestoreTarget::Clipboard,
+        }
+    }
+}
+
+/// 選択が落ち着くまで待ってから記録するテキストを決める
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    // 変化を待っているテキストと、そのテキストになった時刻
+    pending: Option<(String, Instant)>,
+    // 最後に記録した（または記録済みとみなした）テキスト
+    last: Option<String>,
+}
+
+impl Debouncer {
+    /// 読み取ったテキストを渡す。debounce の間変化しなかった新しいテキストであれば返す
+    pub fn observe(&mut self, text: String, now: Instant, debounce: Duration) -> Option<String> {
+        if self.last.as_deref() == Some(text.as_str()) {
+            self.pending = None;
+            return None;
+        }
+
+        let since = match &self.pending {
+            Some((pending, since)) if *pending == text => *since,
+            _ => {
+                self.pending = Some((text, now));
+                now
+            }
+        };
+        if now.duration_since(since) < debounce {
+            return None;
+        }
+
+        let (text, _) = self.pending.take()?;
+        self.last = Some(text.clone());
+        Some(text)
+    }
+
+    /// 記録せずに記録済みとして扱う（監視の停止中や、自分で書き込んだテキスト）
+    pub fn mark_seen(&mut self, text: &str) {
+        self.pending = None;
+        self.last = Some(text.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_waits_for_stable_selection() {
+        let debounce = Duration::from_millis(500);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut debouncer = Debouncer::default();
+
+        // ドラッグ中に選択範囲が広がっていく
+        assert_eq!(debouncer.observe("he".into(), at(0), debounce), None);
+        assert_eq!(debouncer.observe("hell".into(), at(200), debounce), None);
+        assert_eq!(debouncer.observe("hello".into(), at(400), debounce), None);
+        assert_eq!(debouncer.observe("hello".into(), at(700), debounce), None);
+        assert_eq!(
+            debouncer.observe("hello".into(), at(900), debounce),
+            Some("hello".to_string())
+        );
+
+        // 記録済みのテキストは再度記録しない
+        assert_eq!(debouncer.observe("hello".into(), at(2000), debounce), None);
+
+        debouncer.mark_seen("mine");
+        assert_eq!(debouncer.observe("mine".into(), at(3000), debounce), None);
+        assert_eq!(
+            debouncer.observe("world".into(), at(3000), Duration::ZERO),
+            Some("world".to_string())
+        );
+    }
+}
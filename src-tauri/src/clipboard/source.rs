@@ -0,0 +1,323 @@
+// クリップボードにコピーしたアプリケーション（コピー元）の判別
+
+use crate::models::Selection;
+
+/// コピー元のアプリケーション
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceApp {
+    // X11 では WM_CLASS のクラス名（取得できない場合はプロセス名）
+    pub name: String,
+    pub pid: Option<u32>,
+    pub title: Option<String>,
+}
+
+/// 現在クリップボードを所有しているアプリケーションを調べる。
+/// プラットフォームごとに実装を追加する
+pub trait SourceDetector: Send + Sync {
+    fn clipboard_owner(&self) -> Option<SourceApp>;
+
+    /// PRIMARY セレクション（Linux）を所有しているアプリケーション
+    fn primary_owner(&self) -> Option<SourceApp> {
+        None
+    }
+
+    /// セレクションの現在の内容に「履歴に残さない」印（exclusion::CONCEALED_TYPES）が付いているか
+    fn is_concealed(&self, _selection: Selection) -> bool {
+        false
+    }
+}
+
+/// コピー元を判別できない環境用
+pub struct NoSourceDetector;
+
+impl SourceDetector for NoSourceDetector {
+    fn clipboard_owner(&self) -> Option<SourceApp> {
+        None
+    }
+}
+
+/// 現在の環境で使える SourceDetector を返す
+pub fn detector() -> Box<dyn SourceDetector> {
+    #[cfg(target_os = "linux")]
+    {
+        match x11::X11SourceDetector::new() {
+            Ok(detector) => return Box::new(detector),
+            Err(e) => eprintln!("Source application detection is unavailable: {e}"),
+        }
+    }
+
+    Box::new(NoSourceDetector)
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::{SourceApp, SourceDetector};
+    use crate::clipboard::exclusion::CONCEALED_TYPES;
+    use crate::models::Selection;
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ConnectionExt, CreateWindowAux, Window, WindowClass,
+    };
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+
+    // クリップボードの形式一覧（TARGETS）の応答を待つ時間
+    const TARGETS_TIMEOUT: Duration = Duration::from_millis(200);
+
+    // CLIPBOARD セレクションの所有者ウィンドウから WM_CLASS と _NET_WM_PID を読む。
+    // 所有者が非表示のウィンドウの場合は WM_CLIENT_LEADER（アプリケーションの代表ウィンドウ）もたどる
+    pub struct X11SourceDetector {
+        conn: RustConnection,
+        root: Window,
+        // TARGETS を受け取るための非表示ウィンドウ
+        window: Window,
+        clipboard: Atom,
+        primary: Atom,
+        targets: Atom,
+        net_wm_pid: Atom,
+        net_wm_name: Atom,
+        net_active_window: Atom,
+        utf8_string: Atom,
+        wm_client_leader: Atom,
+        concealed_types: Vec<Atom>,
+    }
+
+    fn intern(conn: &RustConnection, name: &[u8]) -> Result<Atom, String> {
+        Ok(conn
+            .intern_atom(false, name)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .atom)
+    }
+
+    impl X11SourceDetector {
+        pub fn new() -> Result<Self, String> {
+            let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+            let screen = &conn.setup().roots[screen_num];
+            let root = screen.root;
+            let root_visual = screen.root_visual;
+
+            let window = conn.generate_id().map_err(|e| e.to_string())?;
+            conn.create_window(
+                x11rb::COPY_DEPTH_FROM_PARENT,
+                window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                root_visual,
+                &CreateWindowAux::new(),
+            )
+            .map_err(|e| e.to_string())?;
+
+            let concealed_types = CONCEALED_TYPES
+                .iter()
+                .map(|name| intern(&conn, name.as_bytes()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Self {
+                root,
+                window,
+                clipboard: intern(&conn, b"CLIPBOARD")?,
+                primary: AtomEnum::PRIMARY.into(),
+                targets: intern(&conn, b"TARGETS")?,
+                net_wm_pid: intern(&conn, b"_NET_WM_PID")?,
+                net_wm_name: intern(&conn, b"_NET_WM_NAME")?,
+                net_active_window: intern(&conn, b"_NET_ACTIVE_WINDOW")?,
+                utf8_string: intern(&conn, b"UTF8_STRING")?,
+                wm_client_leader: intern(&conn, b"WM_CLIENT_LEADER")?,
+                concealed_types,
+                conn,
+            })
+        }
+
+        fn property32(&self, window: Window, property: Atom, type_: AtomEnum) -> Option<u32> {
+            self.conn
+                .get_property(false, window, property, type_, 0, 1)
+                .ok()?
+                .reply()
+                .ok()?
+                .value32()?
+                .next()
+        }
+
+        fn property_bytes(&self, window: Window, property: Atom, type_: Atom) -> Option<Vec<u8>> {
+            let reply = self
+                .conn
+                .get_property(false, window, property, type_, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+            Some(reply.value).filter(|value| !value.is_empty())
+        }
+
+        fn wm_class(&self, window: Window) -> Option<String> {
+            let value =
+                self.property_bytes(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())?;
+            parse_wm_class(&value)
+        }
+
+        fn title(&self, window: Window) -> Option<String> {
+            let value = self
+                .property_bytes(window, self.net_wm_name, self.utf8_string)
+                .or_else(|| {
+                    self.property_bytes(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into())
+                })?;
+            Some(String::from_utf8_lossy(&value).into_owned())
+        }
+
+        fn describe(&self, window: Window) -> Option<SourceApp> {
+            let pid = self.property32(window, self.net_wm_pid, AtomEnum::CARDINAL);
+            let name = self
+                .wm_class(window)
+                .or_else(|| pid.and_then(process_name))?;
+            Some(SourceApp {
+                name,
+                pid,
+                title: self.title(window),
+            })
+        }
+
+        // X サーバーからの応答を受信できるまで最大 timeout 待つ
+        fn wait_readable(&self, timeout: Duration) -> bool {
+            let mut fd = libc::pollfd {
+                fd: self.conn.stream().as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let millis = timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+            // SAFETY: 有効な pollfd を 1 つだけ渡している
+            unsafe { libc::poll(&mut fd, 1, millis) > 0 }
+        }
+
+        // セレクションの所有者に TARGETS を要求して、提供されている形式の一覧を受け取る
+        fn selection_targets(&self, selection: Atom) -> Option<Vec<Atom>> {
+            self.conn
+                .convert_selection(
+                    self.window,
+                    selection,
+                    self.targets,
+                    self.targets,
+                    x11rb::CURRENT_TIME,
+                )
+                .ok()?;
+            self.conn.flush().ok()?;
+
+            let deadline = Instant::now() + TARGETS_TIMEOUT;
+            loop {
+                match self.conn.poll_for_event().ok()? {
+                    Some(Event::SelectionNotify(event)) if event.requestor == self.window => {
+                        if event.property == x11rb::NONE {
+                            return None;
+                        }
+                        break;
+                    }
+                    Some(_) => {}
+                    None => {
+                        let remaining = deadline.checked_duration_since(Instant::now())?;
+                        if remaining.is_zero() || !self.wait_readable(remaining) {
+                            return None;
+                        }
+                    }
+                }
+            }
+
+            let reply = self
+                .conn
+                .get_property(true, self.window, self.targets, AtomEnum::ATOM, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+            let targets = reply.value32()?.collect();
+            Some(targets)
+        }
+    }
+
+    // WM_CLASS は "インスタンス名\0クラス名\0" の形式。クラス名を優先する
+    pub(super) fn parse_wm_class(value: &[u8]) -> Option<String> {
+        let mut parts = value
+            .split(|&b| b == 0)
+            .filter(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned());
+        let instance = parts.next()?;
+        Some(parts.next().unwrap_or(instance))
+    }
+
+    // /proc からプロセス名を取得する
+    fn process_name(pid: u32) -> Option<String> {
+        let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+        Some(name.trim().to_string()).filter(|name| !name.is_empty())
+    }
+
+    impl X11SourceDetector {
+        fn selection_owner(&self, selection: Atom) -> Option<SourceApp> {
+            let owner = self
+                .conn
+                .get_selection_owner(selection)
+                .ok()?
+                .reply()
+                .ok()?
+                .owner;
+            if owner == x11rb::NONE {
+                return None;
+            }
+
+            let mut app = self.describe(owner).or_else(|| {
+                let leader = self.property32(owner, self.wm_client_leader, AtomEnum::WINDOW)?;
+                self.describe(leader)
+            })?;
+
+            // 所有者のウィンドウにタイトルがない場合は、コピー操作をしたアクティブなウィンドウのタイトルを使う
+            if app.title.is_none() {
+                app.title = self
+                    .property32(self.root, self.net_active_window, AtomEnum::WINDOW)
+                    .filter(|&window| window != x11rb::NONE)
+                    .and_then(|window| self.title(window));
+            }
+            Some(app)
+        }
+    }
+
+    impl SourceDetector for X11SourceDetector {
+        fn clipboard_owner(&self) -> Option<SourceApp> {
+            self.selection_owner(self.clipboard)
+        }
+
+        fn primary_owner(&self) -> Option<SourceApp> {
+            self.selection_owner(self.primary)
+        }
+
+        fn is_concealed(&self, selection: Selection) -> bool {
+            let selection = match selection {
+                Selection::Clipboard => self.clipboard,
+                Selection::Primary => self.primary,
+            };
+            self.selection_targets(selection).is_some_and(|targets| {
+                targets
+                    .iter()
+                    .any(|target| self.concealed_types.contains(target))
+            })
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::x11::parse_wm_class;
+
+    #[test]
+    fn test_parse_wm_class() {
+        assert_eq!(
+            parse_wm_class(b"Navigator\0firefox\0"),
+            Some("firefox".to_string())
+        );
+        assert_eq!(parse_wm_class(b"xterm\0"), Some("xterm".to_string()));
+        assert_eq!(parse_wm_class(b""), None);
+    }
+}
@@ -0,0 +1,333 @@
+// メモリ上のクリップボードで ClipboardMonitor を動かし、データベースに保存される内容を確認する
+
+use super::clipboard_source::{ClipboardContent, ClipboardImage, MemoryClipboard};
+use super::primary::PrimarySelection;
+use super::source::{NoSourceDetector, SourceApp, SourceDetector};
+use super::ClipboardMonitor;
+use crate::db::Database;
+use crate::events::{HistoryEvent, HistoryEvents};
+use crate::models::{ClipboardFormat, ClipboardItemType, FormatData, Selection};
+use crate::settings::RestoreTarget;
+use rusqlite::Connection;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::Notify;
+
+struct Harness {
+    clipboard: MemoryClipboard,
+    db: Arc<Mutex<Database>>,
+    monitor: ClipboardMonitor,
+    events: UnboundedReceiver<HistoryEvent>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self::with_detector(Box::new(NoSourceDetector))
+    }
+
+    fn with_detector(source_detector: Box<dyn SourceDetector>) -> Self {
+        let clipboard = MemoryClipboard::default();
+        let db = Arc::new(Mutex::new(
+            Database::from_connection(Connection::open_in_memory().unwrap()).unwrap(),
+        ));
+        let (sender, events) = HistoryEvents::channel();
+        let monitor = ClipboardMonitor::with_source(
+            db.clone(),
+            Arc::new(Notify::new()),
+            sender,
+            Box::new(clipboard.clone()),
+            source_detector,
+        );
+        Self {
+            clipboard,
+            db,
+            monitor,
+            events,
+        }
+    }
+
+    // 前回の呼び出し以降に通知されたイベントと対象のアイテムの内容
+    fn take_events(&mut self) -> Vec<(&'static str, String)> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            let content = match &event {
+                HistoryEvent::ItemAdded(item) | HistoryEvent::ItemUpdated(item) => {
+                    item.content.clone()
+                }
+                HistoryEvent::ItemDeleted(deleted) => deleted.ids.join(","),
+                HistoryEvent::HistoryCleared => String::new(),
+            };
+            events.push((event.name(), content));
+        }
+        events
+    }
+
+    // 他のアプリケーションでテキストをコピーし、監視処理を一度実行する
+    fn copy_text(&self, text: &str) -> bool {
+        self.clipboard.copy_text(text);
+        self.monitor.poll()
+    }
+
+    // 保存されたアイテムの内容（順序は問わない）
+    fn contents(&self) -> Vec<String> {
+        let mut contents: Vec<_> = self
+            .db
+            .lock()
+            .unwrap()
+            .get_all_items()
+            .unwrap()
+            .into_iter()
+            .map(|item| item.content)
+            .collect();
+        contents.sort();
+        contents
+    }
+
+    // contents() と同じ順序でのコピー回数
+    fn copy_counts(&self) -> Vec<i64> {
+        let mut items = self.db.lock().unwrap().get_all_items().unwrap();
+        items.sort_by(|a, b| a.content.cmp(&b.content));
+        items.into_iter().map(|item| item.copy_count).collect()
+    }
+}
+
+#[test]
+fn test_saves_each_distinct_copy() {
+    let harness = Harness::new();
+    assert!(harness.copy_text("first"));
+    assert!(harness.copy_text("second"));
+
+    assert_eq!(harness.contents(), vec!["first", "second"]);
+
+    // 変更がなければ何もしない
+    assert!(!harness.monitor.poll());
+    assert_eq!(harness.contents().len(), 2);
+}
+
+#[test]
+fn test_ignores_consecutive_duplicates() {
+    let harness = Harness::new();
+    harness.copy_text("same");
+    assert!(!harness.copy_text("same"));
+    assert_eq!(harness.contents(), vec!["same"]);
+
+    // 間に別の内容を挟んだ場合は、保存済みのアイテムのコピー回数を増やす
+    harness.copy_text("other");
+    assert!(harness.copy_text("same"));
+    assert_eq!(harness.contents(), vec!["other", "same"]);
+    assert_eq!(harness.copy_counts(), vec![1, 2]);
+}
+
+#[test]
+fn test_ignores_whitespace_only_text() {
+    let harness = Harness::new();
+    harness.copy_text("   ");
+    harness.copy_text("\n\t\n");
+    harness.copy_text("");
+
+    assert!(harness.contents().is_empty());
+}
+
+#[test]
+fn test_does_not_save_while_monitoring_is_disabled() {
+    let harness = Harness::new();
+    harness.copy_text("before");
+
+    harness.monitor.set_monitoring(false);
+    assert!(!harness.copy_text("secret"));
+
+    // 停止中にコピーされた内容は、再開後も保存しない
+    harness.monitor.set_monitoring(true);
+    assert!(!harness.monitor.poll());
+    harness.copy_text("after");
+
+    assert_eq!(harness.contents(), vec!["after", "before"]);
+}
+
+#[test]
+fn test_ignores_own_copies() {
+    let harness = Harness::new();
+    harness.copy_text("external");
+
+    harness.monitor.copy_to_clipboard("from clipedia").unwrap();
+    assert!(!harness.monitor.poll());
+    assert_eq!(
+        harness.clipboard.content(),
+        Some(ClipboardContent::Text {
+            text: "from clipedia".to_string(),
+            formats: Vec::new(),
+        })
+    );
+
+    // 履歴のアイテムをコピーし直しても重複して保存せず、コピー回数だけ増やす
+    let id = harness.db.lock().unwrap().get_all_items().unwrap()[0]
+        .id
+        .clone();
+    harness.monitor.copy_item_to_clipboard(&id, false).unwrap();
+    assert!(!harness.monitor.poll());
+
+    assert_eq!(harness.contents(), vec!["external"]);
+    assert_eq!(harness.copy_counts(), vec![2]);
+}
+
+#[test]
+fn test_saves_html_image_and_files() {
+    let harness = Harness::new();
+    let html = FormatData {
+        format: ClipboardFormat::Html,
+        data: "<b>bold</b>".to_string(),
+    };
+    harness.clipboard.copy(ClipboardContent::Text {
+        text: "bold".to_string(),
+        formats: vec![html.clone()],
+    });
+    assert!(harness.monitor.poll());
+
+    let image = ClipboardImage {
+        width: 2,
+        height: 1,
+        bytes: vec![255, 0, 0, 255, 0, 0, 255, 255],
+    };
+    harness
+        .clipboard
+        .copy(ClipboardContent::Image(image.clone()));
+    assert!(harness.monitor.poll());
+
+    // 同じ画像を再度コピーしても新しいアイテムは作らない
+    harness.clipboard.copy_text("between");
+    harness.monitor.poll();
+    harness.clipboard.copy(ClipboardContent::Image(image));
+    harness.monitor.poll();
+
+    let path = std::env::temp_dir().join("clipedia-monitor-test.txt");
+    harness
+        .clipboard
+        .copy(ClipboardContent::Files(vec![PathBuf::from(&path)]));
+    assert!(harness.monitor.poll());
+
+    let db = harness.db.lock().unwrap();
+    let items = db.get_all_items().unwrap();
+    assert_eq!(items.len(), 4);
+
+    let text = items.iter().find(|item| item.content == "bold").unwrap();
+    assert_eq!(db.get_formats(&text.id).unwrap(), vec![html]);
+
+    let images: Vec<_> = items
+        .iter()
+        .filter(|item| matches!(item.item_type, ClipboardItemType::Image))
+        .collect();
+    assert_eq!(images.len(), 1);
+    assert!(db.get_image_data(&images[0].id).unwrap().is_some());
+
+    assert!(items
+        .iter()
+        .any(|item| matches!(item.item_type, ClipboardItemType::File)));
+}
+
+#[test]
+fn test_primary_selection_capture_and_restore() {
+    let primary = MemoryClipboard::default();
+    let Harness {
+        clipboard,
+        db,
+        monitor,
+        ..
+    } = Harness::new();
+    let monitor = monitor.with_primary(Box::new(primary.clone()));
+
+    // 記録しない設定（デフォルト）では保存しない
+    primary.copy_text("ignored");
+    assert!(!monitor.poll());
+
+    monitor.set_primary_selection(PrimarySelection {
+        capture: true,
+        debounce: Duration::ZERO,
+        restore_target: RestoreTarget::Clipboard,
+    });
+    primary.copy_text("selected");
+    assert!(monitor.poll());
+
+    let items = db.lock().unwrap().get_all_items().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].content, "selected");
+    assert_eq!(items[0].selection, Selection::Primary);
+
+    // 両方に書き込み、書き込んだ内容は再度保存しない
+    clipboard.copy_text("other");
+    monitor.poll();
+    monitor
+        .copy_item_to(&items[0].id, false, RestoreTarget::Both)
+        .unwrap();
+    let expected = Some(ClipboardContent::Text {
+        text: "selected".to_string(),
+        formats: Vec::new(),
+    });
+    assert_eq!(clipboard.content(), expected);
+    assert_eq!(primary.content(), expected);
+    assert!(!monitor.poll());
+    assert_eq!(db.lock().unwrap().get_total_count().unwrap(), 2);
+}
+
+// PRIMARY セレクションの内容にだけ「履歴に残さない」印が付いている
+struct ConcealedPrimary;
+
+impl SourceDetector for ConcealedPrimary {
+    fn clipboard_owner(&self) -> Option<SourceApp> {
+        None
+    }
+
+    fn is_concealed(&self, selection: Selection) -> bool {
+        selection == Selection::Primary
+    }
+}
+
+#[test]
+fn test_ignores_concealed_primary_selection() {
+    let harness = Harness::with_detector(Box::new(ConcealedPrimary));
+    let primary = MemoryClipboard::default();
+    let monitor = harness.monitor.with_primary(Box::new(primary.clone()));
+    monitor.set_primary_selection(PrimarySelection {
+        capture: true,
+        debounce: Duration::ZERO,
+        restore_target: RestoreTarget::Clipboard,
+    });
+
+    primary.copy_text("password");
+    monitor.poll();
+    harness.clipboard.copy_text("copied");
+    monitor.poll();
+
+    let items = harness.db.lock().unwrap().get_all_items().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].content, "copied");
+}
+
+#[test]
+fn test_notifies_history_changes() {
+    let mut harness = Harness::new();
+    harness.copy_text("first");
+    harness.copy_text("second");
+    harness.copy_text("first");
+    assert_eq!(
+        harness.take_events(),
+        vec![
+            ("item-added", "first".to_string()),
+            ("item-added", "second".to_string()),
+            ("item-updated", "first".to_string()),
+        ]
+    );
+
+    // 履歴からコピーした場合もコピー回数が変わったことを通知する
+    harness.monitor.copy_to_clipboard("second").unwrap();
+    assert_eq!(
+        harness.take_events(),
+        vec![("item-updated", "second".to_string())]
+    );
+
+    // 保存しない内容では通知しない
+    harness.copy_text("   ");
+    assert!(!harness.monitor.poll());
+    assert!(harness.take_events().is_empty());
+}
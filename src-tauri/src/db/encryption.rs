@@ -0,0 +1,255 @@
+use super::{migrations, Database};
+use crate::error::{ClipediaError, Result};
+use rusqlite::{Connection, DatabaseName};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+// SQLCipher によるデータベース全体の暗号化
+// パスフレーズからの鍵導出は SQLCipher（PBKDF2-HMAC-SHA512）が行う
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    pub encrypted: bool,
+    pub locked: bool,
+}
+
+fn encryption_error(message: &str) -> ClipediaError {
+    ClipediaError::Encryption(message.to_string())
+}
+
+// パスフレーズを指定して接続を開き、復号できることを確認する
+fn open_with_passphrase(path: &Path, passphrase: &str) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "key", passphrase)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+        .map_err(|_| ClipediaError::WrongPassphrase)?;
+    Ok(conn)
+}
+
+impl Database {
+    pub fn encryption_status(&self) -> EncryptionStatus {
+        EncryptionStatus {
+            encrypted: self.encrypted,
+            locked: self.conn.is_none(),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.conn.is_none()
+    }
+
+    fn file_path(&self) -> Result<&PathBuf> {
+        self.path
+            .as_ref()
+            .ok_or_else(|| encryption_error("in-memory database cannot be encrypted"))
+    }
+
+    /// パスフレーズでロックを解除する
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        if !self.encrypted {
+            return Err(encryption_error("database is not encrypted"));
+        }
+        if self.conn.is_some() {
+            return Ok(());
+        }
+
+        let conn = open_with_passphrase(self.file_path()?, passphrase)?;
+        Self::prepare_connection(&conn)?;
+        self.conn = Some(conn);
+        Ok(())
+    }
+
+    /// 接続を閉じてロックする。再度使うには unlock が必要
+    pub fn lock(&mut self) -> Result<()> {
+        if !self.encrypted {
+            return Err(encryption_error("database is not encrypted"));
+        }
+        self.conn = None;
+        Ok(())
+    }
+
+    /// 暗号化されていないデータベースを暗号化する。
+    /// 暗号化したコピーを作成してから元のファイルと置き換える
+    pub fn enable_encryption(&mut self, passphrase: &str) -> Result<()> {
+        self.encrypt_with(passphrase, |from, to| std::fs::rename(from, to))
+    }
+
+    // rename はファイルの置き換え（テストで失敗させられるようにする）
+    fn encrypt_with(
+        &mut self,
+        passphrase: &str,
+        rename: impl FnOnce(&Path, &Path) -> std::io::Result<()>,
+    ) -> Result<()> {
+        if self.encrypted {
+            return Err(encryption_error("database is already encrypted"));
+        }
+        if passphrase.is_empty() {
+            return Err(encryption_error("passphrase must not be empty"));
+        }
+
+        let path = self.file_path()?.clone();
+        let temp_path = path.with_extension("db.encrypting");
+        if temp_path.exists() {
+            std::fs::remove_file(&temp_path).map_err(|e| encryption_error(&e.to_string()))?;
+        }
+
+        {
+            let conn = self.conn()?;
+            conn.execute(
+                "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+                (temp_path.to_string_lossy(), passphrase),
+            )?;
+            let exported = conn
+                .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+                .and_then(|_| {
+                    let version = migrations::current_version(conn)?;
+                    conn.pragma_update(
+                        Some(DatabaseName::Attached("encrypted")),
+                        "user_version",
+                        version,
+                    )
+                });
+            conn.execute("DETACH DATABASE encrypted", [])?;
+            if let Err(e) = exported {
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(e.into());
+            }
+        }
+
+        // 元の接続を閉じてからファイルを置き換える（Windows では開いたままのファイルを置き換えられない）
+        self.conn = None;
+        if let Err(e) = rename(&temp_path, &path) {
+            // 暗号化していない元のファイルを開き直し、暗号化したコピーは削除する
+            let _ = std::fs::remove_file(&temp_path);
+            let conn = Connection::open(&path)?;
+            Self::prepare_connection(&conn)?;
+            self.conn = Some(conn);
+            return Err(encryption_error(&e.to_string()));
+        }
+
+        self.encrypted = true;
+        let conn = open_with_passphrase(&path, passphrase)?;
+        Self::prepare_connection(&conn)?;
+
+        // 全文検索インデックスを作り直す（外部コンテンツの rowid がコピーで変わる可能性があるため）
+        conn.execute(
+            "INSERT INTO clipboard_fts (clipboard_fts) VALUES ('rebuild')",
+            [],
+        )?;
+
+        self.conn = Some(conn);
+        Ok(())
+    }
+
+    /// パスフレーズを変更する
+    pub fn change_passphrase(&mut self, current: &str, new: &str) -> Result<()> {
+        if !self.encrypted {
+            return Err(encryption_error("database is not encrypted"));
+        }
+        if new.is_empty() {
+            return Err(encryption_error("passphrase must not be empty"));
+        }
+
+        // 現在のパスフレーズが正しいことを確認する
+        drop(open_with_passphrase(self.file_path()?, current)?);
+
+        if self.conn.is_none() {
+            self.unlock(current)?;
+        }
+        self.conn()?.pragma_update(None, "rekey", new)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ClipboardItem, ClipboardItemType};
+
+    struct TempDb(PathBuf);
+
+    impl TempDb {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("clipedia-{}.db", uuid::Uuid::new_v4()));
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_encrypt_lock_and_unlock() {
+        let temp = TempDb::new();
+        let mut db = Database::new(&temp.0).unwrap();
+        let item = ClipboardItem::new("secret note".to_string(), ClipboardItemType::Text);
+        db.insert_item(&item).unwrap();
+
+        db.enable_encryption("correct horse").unwrap();
+        assert!(db.encryption_status().encrypted);
+        assert_eq!(db.get_total_count().unwrap(), 1);
+        assert_eq!(
+            db.search_items("secret", false, true, None).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            db.search_ranked("secret", false, true, 10).unwrap().len(),
+            1
+        );
+        drop(db);
+
+        // 暗号化後はパスフレーズなしでは読めない
+        let raw = std::fs::read(&temp.0).unwrap();
+        assert!(!raw.windows(11).any(|w| w == b"secret note"));
+
+        let mut db = Database::new(&temp.0).unwrap();
+        assert!(db.is_locked());
+        assert!(db.get_total_count().is_err());
+        assert!(db.unlock("wrong").is_err());
+        db.unlock("correct horse").unwrap();
+        assert_eq!(db.get_total_count().unwrap(), 1);
+
+        db.lock().unwrap();
+        assert!(db.get_all_items().is_err());
+
+        db.change_passphrase("correct horse", "battery staple")
+            .unwrap();
+        drop(db);
+
+        let mut db = Database::new(&temp.0).unwrap();
+        assert!(db.unlock("correct horse").is_err());
+        db.unlock("battery staple").unwrap();
+        assert_eq!(db.get_all_items().unwrap()[0].content, "secret note");
+    }
+
+    #[test]
+    fn test_failed_replace_keeps_plaintext_database() {
+        let temp = TempDb::new();
+        let mut db = Database::new(&temp.0).unwrap();
+        let item = ClipboardItem::new("note".to_string(), ClipboardItemType::Text);
+        db.insert_item(&item).unwrap();
+
+        let error = db
+            .encrypt_with("correct horse", |_, _| {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "file is in use",
+                ))
+            })
+            .unwrap_err();
+        assert!(matches!(error, ClipediaError::Encryption(_)));
+
+        // 元のデータベースをそのまま使い続けられる
+        let status = db.encryption_status();
+        assert!(!status.encrypted && !status.locked);
+        assert_eq!(db.get_total_count().unwrap(), 1);
+        assert!(!temp.0.with_extension("db.encrypting").exists());
+
+        db.enable_encryption("correct horse").unwrap();
+        assert_eq!(db.get_total_count().unwrap(), 1);
+    }
+}
@@ -0,0 +1,158 @@
+// FTS5 検索用のヘルパー
+
+// highlight() / snippet() に渡すマーカー。通常のテキストには現れない制御文字を使う
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+
+enum Token {
+    Term(String),
+    Operator(&'static str),
+}
+
+/// ユーザー入力を FTS5 の MATCH 式に変換する。
+///
+/// - `"foo bar"` はフレーズ検索
+/// - `foo*` は前方一致検索
+/// - `AND` / `OR` / `NOT` はブール演算子（大文字のみ）
+/// - それ以外の語はクォートして FTS5 の記号として解釈されないようにする
+///
+/// 検索語が一つもない場合は `None` を返す。
+pub fn build_match_query(input: &str) -> Option<String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '"' {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            if !phrase.trim().is_empty() {
+                tokens.push(Token::Term(quote(phrase.trim())));
+            }
+            continue;
+        }
+
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '"' {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+
+        match word.as_str() {
+            "AND" => tokens.push(Token::Operator("AND")),
+            "OR" => tokens.push(Token::Operator("OR")),
+            "NOT" => tokens.push(Token::Operator("NOT")),
+            _ => {
+                let (body, is_prefix) = match word.strip_suffix('*') {
+                    Some(body) => (body.trim_end_matches('*'), true),
+                    None => (word.as_str(), false),
+                };
+                if body.is_empty() {
+                    continue;
+                }
+                let mut term = quote(body);
+                if is_prefix {
+                    term.push('*');
+                }
+                tokens.push(Token::Term(term));
+            }
+        }
+    }
+
+    // 演算子は検索語に挟まれている場合のみ有効にする（構文エラーを防ぐため）
+    let mut parts: Vec<String> = Vec::new();
+    let mut pending_operator: Option<&'static str> = None;
+    for token in tokens {
+        match token {
+            Token::Operator(op) => {
+                if !parts.is_empty() {
+                    pending_operator = Some(op);
+                }
+            }
+            Token::Term(term) => {
+                if let Some(op) = pending_operator.take() {
+                    parts.push(op.to_string());
+                }
+                parts.push(term);
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// マーカー付きの文字列からマーカーを取り除き、一致箇所の範囲（文字単位）を返す
+pub fn parse_highlighted(marked: &str) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(marked.len());
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut position = 0;
+
+    for c in marked.chars() {
+        match c {
+            MATCH_START => start = Some(position),
+            MATCH_END => {
+                if let Some(s) = start.take() {
+                    ranges.push((s, position));
+                }
+            }
+            _ => {
+                text.push(c);
+                position += 1;
+            }
+        }
+    }
+
+    (text, ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_match_query() {
+        assert_eq!(build_match_query("hello"), Some("\"hello\"".to_string()));
+        assert_eq!(
+            build_match_query("foo bar"),
+            Some("\"foo\" \"bar\"".to_string())
+        );
+        assert_eq!(build_match_query("cli*"), Some("\"cli\"*".to_string()));
+        assert_eq!(
+            build_match_query("\"hello world\" OR rust"),
+            Some("\"hello world\" OR \"rust\"".to_string())
+        );
+        assert_eq!(
+            build_match_query("NOT foo AND bar NOT"),
+            Some("\"foo\" AND \"bar\"".to_string())
+        );
+        assert_eq!(
+            build_match_query("a:b (c)"),
+            Some("\"a:b\" \"(c)\"".to_string())
+        );
+        assert_eq!(build_match_query("  * \"\" OR "), None);
+    }
+
+    #[test]
+    fn test_parse_highlighted() {
+        let marked = format!("say {MATCH_START}hello{MATCH_END} to {MATCH_START}日本{MATCH_END}");
+        let (text, ranges) = parse_highlighted(&marked);
+        assert_eq!(text, "say hello to 日本");
+        assert_eq!(ranges, vec![(4, 9), (13, 15)]);
+    }
+}
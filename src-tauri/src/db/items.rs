@@ -0,0 +1,422 @@
+use super::Database;
+use crate::error::Result;
+use crate::models::{ClipboardItem, ClipboardItemType, FileEntry, ImageInfo, Selection};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, Params, Row};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// アイテムを読み込むクエリで SELECT する列（clipboard_items を c として参照する）。
+/// load_items_with で追加の列を読む場合は、この後に続けて SELECT する
+pub(super) const ITEM_COLUMNS: &str = "c.id, c.content, c.item_type, c.timestamp, c.is_pinned, \
+     c.application_source, c.copy_count, c.last_used_at, c.selection";
+
+/// ITEM_COLUMNS の列数（追加の列の最初のインデックス）
+pub(super) const ITEM_COLUMN_COUNT: usize = 9;
+
+// IN (...) に一度に渡す ID の数（SQLite のパラメーター数の上限より十分小さくする）
+const BATCH_SIZE: usize = 500;
+
+/// 読み込めなかった行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MalformedRow {
+    // ID も読めなかった場合は None
+    pub id: Option<String>,
+    pub reason: String,
+}
+
+/// 読み込んだ行と、読み込めずに飛ばした行
+#[derive(Debug)]
+pub struct LoadedItems<T> {
+    pub rows: Vec<T>,
+    pub malformed: Vec<MalformedRow>,
+}
+
+// ClipboardItem に変換する前の行
+struct RawItem {
+    id: String,
+    content: String,
+    item_type: String,
+    timestamp: String,
+    is_pinned: bool,
+    application_source: Option<String>,
+    copy_count: i64,
+    last_used_at: Option<String>,
+    selection: String,
+}
+
+impl RawItem {
+    fn read(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            content: row.get(1)?,
+            item_type: row.get(2)?,
+            timestamp: row.get(3)?,
+            is_pinned: row.get(4)?,
+            application_source: row.get(5)?,
+            copy_count: row.get(6)?,
+            last_used_at: row.get(7)?,
+            selection: row.get(8)?,
+        })
+    }
+
+    // タグ・画像・ファイルは後からまとめて設定する
+    fn into_item(self) -> std::result::Result<ClipboardItem, MalformedRow> {
+        let timestamp = DateTime::parse_from_rfc3339(&self.timestamp)
+            .map_err(|e| MalformedRow {
+                id: Some(self.id.clone()),
+                reason: format!("invalid timestamp {:?}: {e}", self.timestamp),
+            })?
+            .with_timezone(&Utc);
+        // 最終使用日時は表示と並び替えにしか使わないため、読めなければ未使用として扱う
+        let last_used_at = self
+            .last_used_at
+            .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+            .map(|value| value.with_timezone(&Utc));
+        let item_type = match self.item_type.as_str() {
+            "image" => ClipboardItemType::Image,
+            "file" => ClipboardItemType::File,
+            _ => ClipboardItemType::Text,
+        };
+
+        Ok(ClipboardItem {
+            id: self.id,
+            content: self.content,
+            item_type,
+            timestamp,
+            is_pinned: self.is_pinned,
+            tags: Vec::new(),
+            application_source: self.application_source,
+            image: None,
+            files: None,
+            copy_count: self.copy_count,
+            last_used_at,
+            selection: Selection::from_db(&self.selection),
+        })
+    }
+}
+
+// 型が合わない列を含む行も、ID が読めれば報告できるようにする
+fn read_row<T>(
+    row: &Row,
+    extra: &mut impl FnMut(&Row) -> rusqlite::Result<T>,
+) -> std::result::Result<(ClipboardItem, T), MalformedRow> {
+    let malformed = |e: rusqlite::Error| MalformedRow {
+        id: row.get::<_, String>(0).ok(),
+        reason: e.to_string(),
+    };
+    let raw = RawItem::read(row).map_err(malformed)?;
+    let extra = extra(row).map_err(malformed)?;
+    Ok((raw.into_item()?, extra))
+}
+
+// ids を BATCH_SIZE ずつ `?, ?, ...` に展開してクエリを実行する
+fn query_batched<T>(
+    conn: &Connection,
+    ids: &[&str],
+    sql: impl Fn(&str) -> String,
+    mut f: impl FnMut(&Row) -> rusqlite::Result<T>,
+) -> Result<Vec<T>> {
+    let mut results = Vec::new();
+    for chunk in ids.chunks(BATCH_SIZE) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn.prepare(&sql(&placeholders))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(chunk), &mut f)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        results.extend(rows);
+    }
+    Ok(results)
+}
+
+impl Database {
+    /// ITEM_COLUMNS を SELECT するクエリからアイテムを読み込む。
+    /// 読み込めなかった行はログに出力して飛ばす
+    pub(super) fn load_items<P: Params>(&self, sql: &str, params: P) -> Result<Vec<ClipboardItem>> {
+        let loaded = self.load_items_with(sql, params, |_| Ok(()))?;
+        Ok(loaded.rows.into_iter().map(|(item, ())| item).collect())
+    }
+
+    /// ITEM_COLUMNS に続けて SELECT した列を extra で読み、アイテムと組にして返す。
+    /// タグ・画像・ファイルは読み込んだアイテムの分をまとめて取得する。
+    /// タイムスタンプや列の型が不正な行は飛ばし、malformed に記録する
+    pub(super) fn load_items_with<P: Params, T>(
+        &self,
+        sql: &str,
+        params: P,
+        mut extra: impl FnMut(&Row) -> rusqlite::Result<T>,
+    ) -> Result<LoadedItems<(ClipboardItem, T)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt
+            .query_map(params, |row| Ok(read_row(row, &mut extra)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut loaded = LoadedItems {
+            rows: Vec::with_capacity(rows.len()),
+            malformed: Vec::new(),
+        };
+        for row in rows {
+            match row {
+                Ok(row) => loaded.rows.push(row),
+                Err(malformed) => {
+                    eprintln!(
+                        "Skipped malformed clipboard item {}: {}",
+                        malformed.id.as_deref().unwrap_or("(unknown id)"),
+                        malformed.reason
+                    );
+                    loaded.malformed.push(malformed);
+                }
+            }
+        }
+
+        Self::attach_details(conn, &mut loaded.rows)?;
+        Ok(loaded)
+    }
+
+    // タグ・画像の情報・ファイルの一覧を設定する
+    fn attach_details<T>(conn: &Connection, rows: &mut [(ClipboardItem, T)]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let ids_of = |filter: fn(&ClipboardItemType) -> bool| -> Vec<&str> {
+            rows.iter()
+                .map(|(item, _)| item)
+                .filter(|item| filter(&item.item_type))
+                .map(|item| item.id.as_str())
+                .collect()
+        };
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for (item_id, tag) in query_batched(
+            conn,
+            &ids_of(|_| true),
+            |ids| format!("SELECT item_id, tag FROM tags WHERE item_id IN ({ids}) ORDER BY id"),
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )? {
+            tags.entry(item_id).or_default().push(tag);
+        }
+
+        let images: HashMap<String, ImageInfo> = query_batched(
+            conn,
+            &ids_of(|t| matches!(t, ClipboardItemType::Image)),
+            |ids| {
+                format!(
+                    "SELECT item_id, width, height, byte_size FROM clipboard_images
+                     WHERE item_id IN ({ids})"
+                )
+            },
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    ImageInfo {
+                        width: row.get(1)?,
+                        height: row.get(2)?,
+                        byte_size: row.get(3)?,
+                    },
+                ))
+            },
+        )?
+        .into_iter()
+        .collect();
+
+        let mut files: HashMap<String, Vec<FileEntry>> = HashMap::new();
+        for (item_id, entry) in query_batched(
+            conn,
+            &ids_of(|t| matches!(t, ClipboardItemType::File)),
+            |ids| {
+                format!(
+                    "SELECT item_id, path, file_exists, is_dir, size, mime_type FROM clipboard_files
+                     WHERE item_id IN ({ids})
+                     ORDER BY item_id, position"
+                )
+            },
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    FileEntry {
+                        path: row.get(1)?,
+                        exists: row.get(2)?,
+                        is_dir: row.get(3)?,
+                        size: row.get(4)?,
+                        mime_type: row.get(5)?,
+                    },
+                ))
+            },
+        )? {
+            files.entry(item_id).or_default().push(entry);
+        }
+
+        for (item, _) in rows.iter_mut() {
+            item.tags = tags.remove(&item.id).unwrap_or_default();
+            match item.item_type {
+                ClipboardItemType::Image => item.image = images.get(&item.id).cloned(),
+                ClipboardItemType::File => {
+                    item.files = Some(files.remove(&item.id).unwrap_or_default())
+                }
+                ClipboardItemType::Text => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EncodedImage;
+
+    fn memory_db() -> Database {
+        Database::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn insert_text(db: &Database, content: &str, tags: &[&str]) -> ClipboardItem {
+        let mut item = ClipboardItem::new(content.to_string(), ClipboardItemType::Text);
+        item.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        db.insert_item(&item).unwrap();
+        item
+    }
+
+    fn load_all(db: &Database) -> LoadedItems<(ClipboardItem, ())> {
+        db.load_items_with(
+            &format!("SELECT {ITEM_COLUMNS} FROM clipboard_items c ORDER BY c.content"),
+            [],
+            |_| Ok(()),
+        )
+        .unwrap()
+    }
+
+    fn execute(db: &Database, sql: &str, id: &str) {
+        db.conn().unwrap().execute(sql, [id]).unwrap();
+    }
+
+    #[test]
+    fn test_loads_tags_images_and_files_together() {
+        let db = memory_db();
+        insert_text(&db, "a text", &["work", "todo"]);
+        insert_text(&db, "b text", &[]);
+
+        let image = EncodedImage {
+            info: ImageInfo {
+                width: 2,
+                height: 1,
+                byte_size: 3,
+            },
+            hash: "image-hash".to_string(),
+            png: vec![1, 2, 3],
+            thumbnail_png: vec![4],
+        };
+        let image_item = ClipboardItem::new("[Image 2x1]".to_string(), ClipboardItemType::Image);
+        db.insert_image_item(&image_item, &image).unwrap();
+
+        let files = vec![
+            FileEntry {
+                path: "/tmp/second".to_string(),
+                exists: false,
+                is_dir: false,
+                size: None,
+                mime_type: None,
+            },
+            FileEntry {
+                path: "/tmp/first".to_string(),
+                exists: true,
+                is_dir: true,
+                size: Some(10),
+                mime_type: None,
+            },
+        ];
+        let file_item = ClipboardItem::new("second, first".to_string(), ClipboardItemType::File);
+        db.insert_file_item(&file_item, &files).unwrap();
+
+        let loaded = load_all(&db);
+        assert!(loaded.malformed.is_empty());
+        let items: HashMap<_, _> = loaded
+            .rows
+            .into_iter()
+            .map(|(item, _)| (item.content.clone(), item))
+            .collect();
+
+        assert_eq!(items["a text"].tags, vec!["work", "todo"]);
+        assert!(items["b text"].tags.is_empty());
+        assert!(items["b text"].files.is_none());
+
+        assert_eq!(items["[Image 2x1]"].image.as_ref().unwrap().width, 2);
+        assert!(items["[Image 2x1]"].files.is_none());
+
+        // ファイルは保存した順に並ぶ
+        let paths: Vec<_> = items["second, first"]
+            .files
+            .iter()
+            .flatten()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["/tmp/second", "/tmp/first"]);
+    }
+
+    #[test]
+    fn test_skips_and_reports_malformed_rows() {
+        let db = memory_db();
+        insert_text(&db, "a good", &["keep"]);
+        let bad_timestamp = insert_text(&db, "b bad timestamp", &[]);
+        let bad_content = insert_text(&db, "c bad content", &[]);
+        let bad_last_used = insert_text(&db, "d bad last used", &[]);
+
+        execute(
+            &db,
+            "UPDATE clipboard_items SET timestamp = 'yesterday' WHERE id = ?1",
+            &bad_timestamp.id,
+        );
+        execute(
+            &db,
+            "UPDATE clipboard_items SET content = X'00FF' WHERE id = ?1",
+            &bad_content.id,
+        );
+        execute(
+            &db,
+            "UPDATE clipboard_items SET last_used_at = 'never' WHERE id = ?1",
+            &bad_last_used.id,
+        );
+
+        let loaded = load_all(&db);
+        let contents: Vec<_> = loaded
+            .rows
+            .iter()
+            .map(|(item, _)| item.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["a good", "d bad last used"]);
+        assert_eq!(loaded.rows[0].0.tags, vec!["keep"]);
+        assert_eq!(loaded.rows[1].0.last_used_at, None);
+
+        let ids: Vec<_> = loaded
+            .malformed
+            .iter()
+            .map(|row| row.id.clone().unwrap())
+            .collect();
+        assert_eq!(ids, vec![bad_timestamp.id.clone(), bad_content.id.clone()]);
+        assert!(loaded.malformed[0].reason.contains("yesterday"));
+
+        // 一覧の取得や検索も壊れた行で失敗しない
+        assert_eq!(db.get_all_items().unwrap().len(), 2);
+        assert!(db.get_item(&bad_timestamp.id).unwrap().is_none());
+        assert_eq!(db.search_items("bad", false, true, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_batches_more_items_than_batch_size() {
+        let db = memory_db();
+        let count = BATCH_SIZE + 20;
+        for i in 0..count {
+            insert_text(&db, &format!("item {i:04}"), &[&format!("tag{i}")]);
+        }
+
+        let loaded = load_all(&db);
+        assert_eq!(loaded.rows.len(), count);
+        assert!(loaded
+            .rows
+            .iter()
+            .enumerate()
+            .all(|(i, (item, _))| item.tags == vec![format!("tag{i}")]));
+    }
+}
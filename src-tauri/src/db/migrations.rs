@@ -0,0 +1,445 @@
+use rusqlite::{ffi, Connection, Error, Result, Transaction};
+use std::collections::HashMap;
+
+// スキーマのバージョンは PRAGMA user_version で管理する。
+// 各マイグレーションは 1 から始まる連番で、配列の順番どおりに適用される。
+// 一度リリースしたマイグレーションは書き換えず、必ず末尾に追加すること。
+struct Migration {
+    version: i64,
+    description: &'static str,
+    up: fn(&Transaction) -> Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        up: v1_initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "full-text search index",
+        up: v2_full_text_search,
+    },
+    Migration {
+        version: 3,
+        description: "image storage",
+        up: v3_images,
+    },
+    Migration {
+        version: 4,
+        description: "file list storage",
+        up: v4_files,
+    },
+    Migration {
+        version: 5,
+        description: "rich text formats",
+        up: v5_formats,
+    },
+    Migration {
+        version: 6,
+        description: "application source index",
+        up: v6_application_source_index,
+    },
+    Migration {
+        version: 7,
+        description: "content hash and usage counts",
+        up: v7_usage_counts,
+    },
+    Migration {
+        version: 8,
+        description: "selection marker",
+        up: v8_selection,
+    },
+    Migration {
+        version: 9,
+        description: "snippets",
+        up: v9_snippets,
+    },
+    Migration {
+        version: 10,
+        description: "keyset pagination index",
+        up: v10_keyset_index,
+    },
+];
+
+/// このバイナリが扱える最新のスキーマバージョン
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> Result<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// 未適用のマイグレーションを順番に適用する。
+/// 各ステップは個別のトランザクションで実行され、失敗した場合はそのステップ全体がロールバックされる。
+pub fn run(conn: &Connection) -> Result<()> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    // 新しいバージョンのアプリで作成されたデータベースは開かない
+    if current > latest {
+        return Err(Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_CANTOPEN),
+            Some(format!(
+                "database schema version {current} is newer than supported version {latest}"
+            )),
+        ));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx).map_err(|e| match e {
+            Error::SqliteFailure(err, msg) => Error::SqliteFailure(
+                err,
+                Some(format!(
+                    "migration v{} ({}) failed: {}",
+                    migration.version,
+                    migration.description,
+                    msg.unwrap_or_default()
+                )),
+            ),
+            other => other,
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+// v1: 初期スキーマ
+// user_version 導入前のデータベースにもテーブルが存在するため IF NOT EXISTS のままにしておく
+fn v1_initial_schema(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS clipboard_items (
+            id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            item_type TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            is_pinned INTEGER NOT NULL DEFAULT 0,
+            application_source TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            FOREIGN KEY (item_id) REFERENCES clipboard_items(id) ON DELETE CASCADE,
+            UNIQUE(item_id, tag)
+        );
+
+        -- タグマスターテーブル（カスタムタグの管理用）
+        CREATE TABLE IF NOT EXISTS tag_master (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT,
+            is_system INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_clipboard_timestamp ON clipboard_items(timestamp);
+        CREATE INDEX IF NOT EXISTS idx_clipboard_pinned ON clipboard_items(is_pinned);",
+    )
+}
+
+// v2: FTS5 による全文検索インデックス
+// clipboard_items を外部コンテンツとして参照し、トリガーで同期する。
+// clipboard_items は INTEGER PRIMARY KEY を持たないため、VACUUM 後は 'rebuild' で再構築すること
+fn v2_full_text_search(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE clipboard_fts USING fts5(
+            content,
+            content = 'clipboard_items',
+            content_rowid = 'rowid',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER clipboard_items_fts_insert AFTER INSERT ON clipboard_items BEGIN
+            INSERT INTO clipboard_fts (rowid, content) VALUES (new.rowid, new.content);
+        END;
+
+        CREATE TRIGGER clipboard_items_fts_delete AFTER DELETE ON clipboard_items BEGIN
+            INSERT INTO clipboard_fts (clipboard_fts, rowid, content)
+                VALUES ('delete', old.rowid, old.content);
+        END;
+
+        CREATE TRIGGER clipboard_items_fts_update AFTER UPDATE OF content ON clipboard_items BEGIN
+            INSERT INTO clipboard_fts (clipboard_fts, rowid, content)
+                VALUES ('delete', old.rowid, old.content);
+            INSERT INTO clipboard_fts (rowid, content) VALUES (new.rowid, new.content);
+        END;
+
+        INSERT INTO clipboard_fts (clipboard_fts) VALUES ('rebuild');",
+    )
+}
+
+// v3: 画像アイテムの本体とサムネイル
+fn v3_images(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE clipboard_images (
+            item_id TEXT PRIMARY KEY,
+            hash TEXT NOT NULL UNIQUE,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            byte_size INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            thumbnail BLOB NOT NULL,
+            FOREIGN KEY (item_id) REFERENCES clipboard_items(id) ON DELETE CASCADE
+        );",
+    )
+}
+
+// v4: ファイルアイテムに含まれるパスの一覧
+fn v4_files(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE clipboard_files (
+            item_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            file_exists INTEGER NOT NULL,
+            is_dir INTEGER NOT NULL,
+            size INTEGER,
+            mime_type TEXT,
+            PRIMARY KEY (item_id, position),
+            FOREIGN KEY (item_id) REFERENCES clipboard_items(id) ON DELETE CASCADE
+        );",
+    )
+}
+
+// v5: HTML や RTF などプレーンテキスト以外の表現
+fn v5_formats(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE clipboard_formats (
+            item_id TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (item_id, mime_type),
+            FOREIGN KEY (item_id) REFERENCES clipboard_items(id) ON DELETE CASCADE
+        );",
+    )
+}
+
+fn v6_application_source_index(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_items_application_source
+            ON clipboard_items(application_source);",
+    )
+}
+
+// v7: 内容のハッシュによる重複排除と、コピーされた回数
+// 既存の重複したアイテムは最新のものにまとめる（タグとピン留めは引き継ぐ）
+fn v7_usage_counts(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clipboard_items ADD COLUMN content_hash TEXT;
+        ALTER TABLE clipboard_items ADD COLUMN copy_count INTEGER NOT NULL DEFAULT 1;
+        ALTER TABLE clipboard_items ADD COLUMN last_used_at TEXT;",
+    )?;
+
+    // 画像は画素データのハッシュ（clipboard_images.hash）を使う
+    let mut stmt = tx.prepare(
+        "SELECT c.id, c.item_type, c.content, i.hash
+         FROM clipboard_items c
+         LEFT JOIN clipboard_images i ON i.item_id = c.id
+         ORDER BY c.timestamp DESC",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut kept: HashMap<String, String> = HashMap::new();
+    for (id, item_type, content, image_hash) in rows {
+        let hash = image_hash.unwrap_or_else(|| super::content_hash(&item_type, &content));
+        match kept.get(&hash) {
+            Some(newer) => {
+                tx.execute(
+                    "INSERT OR IGNORE INTO tags (item_id, tag) SELECT ?1, tag FROM tags WHERE item_id = ?2",
+                    (newer, &id),
+                )?;
+                tx.execute(
+                    "UPDATE clipboard_items
+                     SET copy_count = copy_count + (SELECT copy_count FROM clipboard_items WHERE id = ?2),
+                         is_pinned = MAX(is_pinned, (SELECT is_pinned FROM clipboard_items WHERE id = ?2))
+                     WHERE id = ?1",
+                    (newer, &id),
+                )?;
+                tx.execute("DELETE FROM clipboard_items WHERE id = ?1", [&id])?;
+            }
+            None => {
+                tx.execute(
+                    "UPDATE clipboard_items SET content_hash = ?1 WHERE id = ?2",
+                    (&hash, &id),
+                )?;
+                kept.insert(hash, id);
+            }
+        }
+    }
+
+    tx.execute_batch(
+        "CREATE UNIQUE INDEX idx_clipboard_items_content_hash ON clipboard_items(content_hash);
+        CREATE INDEX idx_clipboard_items_copy_count ON clipboard_items(copy_count);",
+    )
+}
+
+// v8: アイテムを取得したセレクション（'clipboard' または Linux の 'primary'）
+fn v8_selection(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clipboard_items ADD COLUMN selection TEXT NOT NULL DEFAULT 'clipboard';",
+    )
+}
+
+// v9: 名前付きの定型文。履歴とは独立して保存する
+fn v9_snippets(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE snippets (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            abbreviation TEXT UNIQUE,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_snippets_name ON snippets(name);",
+    )
+}
+
+// v10: 一覧のページ送り（ピン留め・時刻・ID の順）用のインデックス。
+// is_pinned だけのインデックスはこのインデックスで代用できるため削除する
+fn v10_keyset_index(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE INDEX idx_clipboard_items_keyset ON clipboard_items(is_pinned, timestamp, id);
+        DROP INDEX IF EXISTS idx_clipboard_pinned;",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // user_version 導入前（v0）のスキーマ
+    const V0_SCHEMA: &str = "
+        CREATE TABLE clipboard_items (
+            id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            item_type TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            is_pinned INTEGER NOT NULL DEFAULT 0,
+            application_source TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            FOREIGN KEY (item_id) REFERENCES clipboard_items(id) ON DELETE CASCADE,
+            UNIQUE(item_id, tag)
+        );
+        CREATE TABLE tag_master (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT,
+            is_system INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        INSERT INTO clipboard_items (id, content, item_type, timestamp, is_pinned)
+            VALUES ('a', 'hello', 'text', '2024-01-01T00:00:00+00:00', 1);
+        INSERT INTO clipboard_items (id, content, item_type, timestamp, is_pinned)
+            VALUES ('b', 'hello', 'text', '2024-01-02T00:00:00+00:00', 0);
+        INSERT INTO tags (item_id, tag) VALUES ('a', 'greeting');
+        INSERT INTO tag_master (name, color, is_system) VALUES ('work', '#000000', 0);
+    ";
+
+    fn v0_fixture() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V0_SCHEMA).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_migrations_are_sequential() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i64 + 1);
+        }
+    }
+
+    #[test]
+    fn test_fresh_database_is_migrated_to_latest() {
+        let conn = Connection::open_in_memory().unwrap();
+        run(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        // 二回目の実行では何も起きない
+        run(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_v0_fixture_is_upgraded_and_keeps_data() {
+        let conn = v0_fixture();
+        assert_eq!(current_version(&conn).unwrap(), 0);
+
+        run(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        // 重複したアイテムは新しい方にまとめられる
+        let (content, is_pinned, copy_count): (String, bool, i64) = conn
+            .query_row(
+                "SELECT content, is_pinned, copy_count FROM clipboard_items WHERE id = 'b'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(content, "hello");
+        assert!(is_pinned);
+        assert_eq!(copy_count, 2);
+
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM clipboard_items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 1);
+
+        let tag: String = conn
+            .query_row("SELECT tag FROM tags WHERE item_id = 'b'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(tag, "greeting");
+
+        let color: String = conn
+            .query_row(
+                "SELECT color FROM tag_master WHERE name = 'work'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(color, "#000000");
+
+        // 既存の行が全文検索インデックスに登録されている
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM clipboard_fts WHERE clipboard_fts MATCH 'hello'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_newer_database_is_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+        assert!(run(&conn).is_err());
+        assert_eq!(current_version(&conn).unwrap(), latest_version() + 1);
+    }
+}
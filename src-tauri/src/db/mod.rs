@@ -1,87 +1,130 @@
-use crate::models::{ClipboardItem, ClipboardItemType};
-use chrono::{DateTime, Utc};
+mod backup;
+mod encryption;
+mod fts;
+mod items;
+mod migrations;
+mod query;
+mod retention;
+mod snippets;
+
+use crate::content_analyzer::SENSITIVE_TAG;
+use crate::error::{ClipediaError, Result};
+use crate::models::{
+    ClipboardFormat, ClipboardItem, EncodedImage, FileEntry, FormatData, ItemOrder, SearchMatch,
+};
+use chrono::Utc;
+use items::{ITEM_COLUMNS, ITEM_COLUMN_COUNT};
 use regex::Regex;
-use rusqlite::{Connection, Result};
-use std::path::Path;
+use rusqlite::{Connection, ErrorCode, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+pub use backup::{
+    parse_export, ExportFilter, ExportFormat, HistoryExport, ImportMode, ImportOptions,
+    ImportReport,
+};
+pub use encryption::EncryptionStatus;
+pub use query::{ItemCursor, ItemFilter, ItemPage, ItemQuery};
+pub use retention::{RetentionPolicy, RetentionReport};
+
+/// アイテムを保存した結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertOutcome {
+    Inserted,
+    // 同じ内容が保存済みだったため、そのアイテム（ID）のコピー回数を増やした
+    Merged(String),
+}
+
+impl InsertOutcome {
+    // 保存先のアイテムの ID
+    pub fn id<'a>(&'a self, inserted_id: &'a str) -> &'a str {
+        match self {
+            InsertOutcome::Inserted => inserted_id,
+            InsertOutcome::Merged(id) => id,
+        }
+    }
+}
+
+/// 重複検出用のハッシュ。画像は画素データのハッシュ（clipboard::image::hash_rgba）を使う
+pub fn content_hash(item_type: &str, content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(item_type.as_bytes());
+    hasher.update([0]);
+    hasher.update(content.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
 
 pub struct Database {
-    conn: Connection,
+    // 暗号化されたデータベースがロックされている間は None
+    conn: Option<Connection>,
+    path: Option<PathBuf>,
+    encrypted: bool,
 }
 
 impl Database {
+    /// データベースを開く。
+    /// 暗号化されている場合はロックされた状態で返すので、unlock で解除すること
     pub fn new(path: &Path) -> Result<Self> {
         let conn = Connection::open(path)?;
-        let db = Self { conn };
-        db.init_tables()?;
+
+        if let Err(e) = conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(())) {
+            if e.sqlite_error_code() == Some(ErrorCode::NotADatabase) {
+                return Ok(Self {
+                    conn: None,
+                    path: Some(path.to_path_buf()),
+                    encrypted: true,
+                });
+            }
+            return Err(e.into());
+        }
+
+        let mut db = Self::from_connection(conn)?;
+        db.path = Some(path.to_path_buf());
         Ok(db)
     }
 
-    fn init_tables(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS clipboard_items (
-                id TEXT PRIMARY KEY,
-                content TEXT NOT NULL,
-                item_type TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                is_pinned INTEGER NOT NULL DEFAULT 0,
-                application_source TEXT,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        )?;
-
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS tags (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                item_id TEXT NOT NULL,
-                tag TEXT NOT NULL,
-                FOREIGN KEY (item_id) REFERENCES clipboard_items(id) ON DELETE CASCADE,
-                UNIQUE(item_id, tag)
-            )",
-            [],
-        )?;
+    pub(crate) fn from_connection(conn: Connection) -> Result<Self> {
+        Self::prepare_connection(&conn)?;
+        Ok(Self {
+            conn: Some(conn),
+            path: None,
+            encrypted: false,
+        })
+    }
 
-        // タグマスターテーブル（カスタムタグの管理用）
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS tag_master (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL UNIQUE,
-                color TEXT,
-                is_system INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        )?;
+    // 開いた接続の初期化（マイグレーションとシステムタグ）
+    fn prepare_connection(conn: &Connection) -> Result<()> {
+        // ON DELETE CASCADE を有効にする
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrations::run(conn)?;
+        query::register_functions(conn)?;
 
         // システムタグを初期化
-        self.init_system_tags()?;
-
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_clipboard_timestamp ON clipboard_items(timestamp)",
-            [],
-        )?;
-
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_clipboard_pinned ON clipboard_items(is_pinned)",
-            [],
-        )?;
+        Self::init_system_tags(conn)
+    }
 
-        Ok(())
+    fn conn(&self) -> Result<&Connection> {
+        self.conn.as_ref().ok_or(ClipediaError::DatabaseLocked)
     }
 
-    fn init_system_tags(&self) -> Result<()> {
+    fn init_system_tags(conn: &Connection) -> Result<()> {
         let system_tags = [
-            ("url", "#3B82F6"),      // blue
-            ("code", "#10B981"),     // green
-            ("json", "#F59E0B"),     // amber
-            ("markdown", "#8B5CF6"), // violet
-            ("email", "#EF4444"),    // red
-            ("phone", "#EC4899"),    // pink
-            ("path", "#6366F1"),     // indigo
+            ("url", "#3B82F6"),       // blue
+            ("code", "#10B981"),      // green
+            ("json", "#F59E0B"),      // amber
+            ("markdown", "#8B5CF6"),  // violet
+            ("email", "#EF4444"),     // red
+            ("phone", "#EC4899"),     // pink
+            ("path", "#6366F1"),      // indigo
+            ("sensitive", "#DC2626"), // dark red
         ];
 
         for (name, color) in system_tags {
-            self.conn.execute(
+            conn.execute(
                 "INSERT OR IGNORE INTO tag_master (name, color, is_system) VALUES (?1, ?2, 1)",
                 (name, color),
             )?;
@@ -89,150 +132,299 @@ impl Database {
         Ok(())
     }
 
-    pub fn insert_item(&self, item: &ClipboardItem) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
+    pub fn insert_item(&self, item: &ClipboardItem) -> Result<InsertOutcome> {
+        let tx = self.conn()?.unchecked_transaction()?;
+        let outcome = Self::insert_item_row(&tx, item, &Self::hash_of(item))?;
+        tx.commit()?;
+        Ok(outcome)
+    }
+
+    // テキストアイテムを HTML などの表現と一緒に保存する
+    pub fn insert_text_item(
+        &self,
+        item: &ClipboardItem,
+        formats: &[FormatData],
+    ) -> Result<InsertOutcome> {
+        let tx = self.conn()?.unchecked_transaction()?;
+        let outcome = Self::insert_item_row(&tx, item, &Self::hash_of(item))?;
+
+        // 保存済みのアイテムの場合も、書式付きの表現は最新のものにする
+        Self::insert_format_rows(&tx, outcome.id(&item.id), formats)?;
+
+        tx.commit()?;
+        Ok(outcome)
+    }
+
+    // プレーンテキスト以外に保存されている表現を取得する
+    pub fn get_formats(&self, item_id: &str) -> Result<Vec<FormatData>> {
+        let mut stmt = self
+            .conn()?
+            .prepare("SELECT mime_type, data FROM clipboard_formats WHERE item_id = ?1")?;
+        let rows = stmt
+            .query_map([item_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // 未知の形式は無視する
+        Ok(rows
+            .into_iter()
+            .filter_map(|(mime_type, data)| {
+                ClipboardFormat::from_mime_type(&mime_type)
+                    .map(|format| FormatData { format, data })
+            })
+            .collect())
+    }
+
+    // 画像アイテムを本体とサムネイルと一緒に保存する
+    pub fn insert_image_item(
+        &self,
+        item: &ClipboardItem,
+        image: &EncodedImage,
+    ) -> Result<InsertOutcome> {
+        let tx = self.conn()?.unchecked_transaction()?;
+        let outcome = Self::insert_item_row(&tx, item, &image.hash)?;
+
+        if outcome == InsertOutcome::Inserted {
+            Self::insert_image_row(&tx, &item.id, image)?;
+        }
+
+        tx.commit()?;
+        Ok(outcome)
+    }
+
+    // ファイルアイテムをパスの一覧と一緒に保存する
+    pub fn insert_file_item(
+        &self,
+        item: &ClipboardItem,
+        files: &[FileEntry],
+    ) -> Result<InsertOutcome> {
+        let tx = self.conn()?.unchecked_transaction()?;
+        let outcome = Self::insert_item_row(&tx, item, &Self::hash_of(item))?;
+
+        if outcome == InsertOutcome::Inserted {
+            Self::insert_file_rows(&tx, &item.id, files)?;
+        }
+
+        tx.commit()?;
+        Ok(outcome)
+    }
+
+    fn insert_format_rows(conn: &Connection, item_id: &str, formats: &[FormatData]) -> Result<()> {
+        for format in formats {
+            conn.execute(
+                "INSERT OR REPLACE INTO clipboard_formats (item_id, mime_type, data)
+                 VALUES (?1, ?2, ?3)",
+                (item_id, format.format.mime_type(), &format.data),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn insert_image_row(conn: &Connection, item_id: &str, image: &EncodedImage) -> Result<()> {
+        conn.execute(
+            "INSERT INTO clipboard_images (item_id, hash, width, height, byte_size, data, thumbnail)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                item_id,
+                &image.hash,
+                image.info.width,
+                image.info.height,
+                image.info.byte_size,
+                &image.png,
+                &image.thumbnail_png,
+            ),
+        )?;
+        Ok(())
+    }
 
-        tx.execute(
-            "INSERT INTO clipboard_items (id, content, item_type, timestamp, is_pinned, application_source)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    fn insert_file_rows(conn: &Connection, item_id: &str, files: &[FileEntry]) -> Result<()> {
+        for (position, file) in files.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO clipboard_files (item_id, position, path, file_exists, is_dir, size, mime_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (
+                    item_id,
+                    position as i64,
+                    &file.path,
+                    file.exists,
+                    file.is_dir,
+                    file.size,
+                    &file.mime_type,
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    // 画像以外のアイテムの重複検出用ハッシュ
+    fn hash_of(item: &ClipboardItem) -> String {
+        content_hash(item.item_type.as_str(), &item.content)
+    }
+
+    // 同じ内容（content_hash）のアイテムが保存済みの場合は、新しい行を追加せずに
+    // そのアイテムのコピー回数を増やして履歴の先頭に移動する。
+    // PRIMARY で記録済みのテキストが CLIPBOARD にコピーされた場合は CLIPBOARD の印に変える
+    fn insert_item_row(
+        conn: &Connection,
+        item: &ClipboardItem,
+        content_hash: &str,
+    ) -> Result<InsertOutcome> {
+        let timestamp = item.timestamp.to_rfc3339();
+        let stored_id: String = conn.query_row(
+            "INSERT INTO clipboard_items
+                (id, content, item_type, timestamp, is_pinned, application_source, content_hash, copy_count,
+                 selection)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(content_hash) DO UPDATE SET
+                copy_count = copy_count + excluded.copy_count,
+                last_used_at = excluded.timestamp,
+                timestamp = MAX(timestamp, excluded.timestamp),
+                selection = CASE excluded.selection WHEN 'clipboard' THEN 'clipboard' ELSE selection END
+             RETURNING id",
             (
                 &item.id,
                 &item.content,
-                match item.item_type {
-                    ClipboardItemType::Text => "text",
-                    ClipboardItemType::Image => "image",
-                    ClipboardItemType::File => "file",
-                },
-                item.timestamp.to_rfc3339(),
+                item.item_type.as_str(),
+                &timestamp,
                 item.is_pinned,
                 &item.application_source,
+                content_hash,
+                item.copy_count.max(1),
+                item.selection.as_str(),
             ),
+            |row| row.get(0),
         )?;
 
+        let outcome = if stored_id == item.id {
+            InsertOutcome::Inserted
+        } else {
+            InsertOutcome::Merged(stored_id)
+        };
+
         for tag in &item.tags {
-            tx.execute(
-                "INSERT INTO tags (item_id, tag) VALUES (?1, ?2)",
-                (&item.id, tag),
+            conn.execute(
+                "INSERT OR IGNORE INTO tags (item_id, tag) VALUES (?1, ?2)",
+                (outcome.id(&item.id), tag),
             )?;
         }
 
-        tx.commit()?;
-        Ok(())
+        Ok(outcome)
     }
 
-    pub fn get_all_items(&self) -> Result<Vec<ClipboardItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, item_type, timestamp, is_pinned, application_source 
-             FROM clipboard_items 
-             ORDER BY is_pinned DESC, timestamp DESC",
+    /// 保存済みの内容が再度コピーされたことを記録する（コピー回数を増やし、履歴の先頭に移動する）。
+    /// 該当するアイテムがあればその ID を返す
+    pub fn record_copy(&self, content_hash: &str) -> Result<Option<String>> {
+        let now = Utc::now().to_rfc3339();
+        Ok(self
+            .conn()?
+            .query_row(
+                "UPDATE clipboard_items
+                 SET copy_count = copy_count + 1, last_used_at = ?1, timestamp = ?1
+                 WHERE content_hash = ?2
+                 RETURNING id",
+                (now, content_hash),
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn get_item(&self, id: &str) -> Result<Option<ClipboardItem>> {
+        let mut items = self.load_items(
+            &format!("SELECT {ITEM_COLUMNS} FROM clipboard_items c WHERE c.id = ?1"),
+            [id],
         )?;
+        Ok(items.pop())
+    }
 
-        let items = stmt
-            .query_map([], |row| {
-                let id: String = row.get(0)?;
-                let content: String = row.get(1)?;
-                let item_type_str: String = row.get(2)?;
-                let timestamp_str: String = row.get(3)?;
-                let is_pinned: bool = row.get(4)?;
-                let application_source: Option<String> = row.get(5)?;
-
-                let item_type = match item_type_str.as_str() {
-                    "image" => ClipboardItemType::Image,
-                    "file" => ClipboardItemType::File,
-                    _ => ClipboardItemType::Text,
-                };
-
-                let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-                    .unwrap()
-                    .with_timezone(&Utc);
-
-                let tags = self.get_tags_for_item(&id).unwrap_or_default();
-
-                Ok(ClipboardItem {
-                    id,
-                    content,
-                    item_type,
-                    timestamp,
-                    is_pinned,
-                    tags,
-                    application_source,
-                })
-            })?
-            .collect::<Result<Vec<_>>>()?;
+    // 画像本体（PNG）を取得する
+    pub fn get_image_data(&self, item_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .conn()?
+            .query_row(
+                "SELECT data FROM clipboard_images WHERE item_id = ?1",
+                [item_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    // サムネイル（PNG）を取得する
+    pub fn get_image_thumbnail(&self, item_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .conn()?
+            .query_row(
+                "SELECT thumbnail FROM clipboard_images WHERE item_id = ?1",
+                [item_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
 
-        Ok(items)
+    pub fn get_all_items(&self) -> Result<Vec<ClipboardItem>> {
+        self.load_items(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 ORDER BY c.is_pinned DESC, c.timestamp DESC"
+            ),
+            [],
+        )
     }
 
-    pub fn get_items_paginated(&self, offset: i64, limit: i64) -> Result<Vec<ClipboardItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, item_type, timestamp, is_pinned, application_source 
-             FROM clipboard_items 
-             ORDER BY is_pinned DESC, timestamp DESC
-             LIMIT ?1 OFFSET ?2",
+    pub fn get_items_paginated(
+        &self,
+        offset: i64,
+        limit: i64,
+        order: ItemOrder,
+    ) -> Result<Vec<ClipboardItem>> {
+        let order_by = match order {
+            ItemOrder::Newest => "c.timestamp DESC",
+            ItemOrder::Oldest => "c.timestamp ASC",
+            ItemOrder::MostUsed => "c.copy_count DESC, c.timestamp DESC",
+        };
+        let items = self.load_items(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 ORDER BY c.is_pinned DESC, {order_by}
+                 LIMIT ?1 OFFSET ?2"
+            ),
+            [limit, offset],
         )?;
 
-        let items = stmt
-            .query_map([limit, offset], |row| {
-                let id: String = row.get(0)?;
-                let content: String = row.get(1)?;
-                let type_str: String = row.get(2)?;
-                let timestamp_str: String = row.get(3)?;
-                let is_pinned: bool = row.get(4)?;
-                let application_source: Option<String> = row.get(5)?;
-
-                let item_type = match type_str.as_str() {
-                    "text" => ClipboardItemType::Text,
-                    "image" => ClipboardItemType::Image,
-                    "file" => ClipboardItemType::File,
-                    _ => ClipboardItemType::Text,
-                };
-
-                let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-                    .unwrap()
-                    .with_timezone(&Utc);
-
-                let tags = self.get_tags_for_item(&id).unwrap_or_default();
-
-                Ok(ClipboardItem {
-                    id,
-                    content,
-                    item_type,
-                    timestamp,
-                    is_pinned,
-                    tags,
-                    application_source,
-                })
-            })?
-            .collect::<Result<Vec<_>>>()?;
+        // 機密情報は伏せ字にして返す
+        Ok(items.into_iter().map(ClipboardItem::masked).collect())
+    }
 
-        Ok(items)
+    /// コピーされた順（新しい順）にアイテムを取得する。ピン留めは考慮しない
+    pub fn get_recent_items(&self, limit: i64) -> Result<Vec<ClipboardItem>> {
+        self.load_items(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 ORDER BY c.timestamp DESC
+                 LIMIT ?1"
+            ),
+            [limit],
+        )
     }
 
     pub fn get_total_count(&self) -> Result<i64> {
         let count: i64 =
-            self.conn
+            self.conn()?
                 .query_row("SELECT COUNT(*) FROM clipboard_items", [], |row| row.get(0))?;
         Ok(count)
     }
 
-    fn get_tags_for_item(&self, item_id: &str) -> Result<Vec<String>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT tag FROM tags WHERE item_id = ?1")?;
-        let tags = stmt
-            .query_map([item_id], |row| row.get(0))?
-            .collect::<Result<Vec<String>>>()?;
-        Ok(tags)
-    }
-
     pub fn delete_item(&self, id: &str) -> Result<()> {
-        self.conn
+        self.conn()?
             .execute("DELETE FROM clipboard_items WHERE id = ?1", [id])?;
         Ok(())
     }
 
     pub fn update_pin_status(&self, id: &str, is_pinned: bool) -> Result<()> {
-        self.conn.execute(
+        self.conn()?.execute(
             "UPDATE clipboard_items SET is_pinned = ?1 WHERE id = ?2",
             (is_pinned, id),
         )?;
@@ -240,7 +432,7 @@ impl Database {
     }
 
     pub fn add_tag(&self, item_id: &str, tag: &str) -> Result<()> {
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT OR IGNORE INTO tags (item_id, tag) VALUES (?1, ?2)",
             (item_id, tag),
         )?;
@@ -248,35 +440,52 @@ impl Database {
     }
 
     pub fn remove_tag(&self, item_id: &str, tag: &str) -> Result<()> {
-        self.conn.execute(
+        self.conn()?.execute(
             "DELETE FROM tags WHERE item_id = ?1 AND tag = ?2",
             (item_id, tag),
         )?;
         Ok(())
     }
 
+    // 保存期間を過ぎた機密情報を削除する（ピン留めされたアイテムは残す）
+    /// 削除したアイテムの ID を返す
+    pub fn delete_expired_sensitive(&self, ttl: chrono::Duration) -> Result<Vec<String>> {
+        let cutoff = (Utc::now() - ttl).to_rfc3339();
+        let mut stmt = self.conn()?.prepare(
+            "DELETE FROM clipboard_items
+             WHERE is_pinned = 0
+               AND timestamp < ?1
+               AND id IN (SELECT item_id FROM tags WHERE tag = ?2)
+             RETURNING id",
+        )?;
+        let ids = stmt
+            .query_map((cutoff, SENSITIVE_TAG), |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ids)
+    }
+
     pub fn clear_all(&self) -> Result<()> {
-        self.conn.execute("DELETE FROM clipboard_items", [])?;
+        self.conn()?.execute("DELETE FROM clipboard_items", [])?;
         Ok(())
     }
 
     // タグマスター管理用メソッド
     pub fn get_all_tags(&self) -> Result<Vec<(String, Option<String>, bool)>> {
         let mut stmt = self
-            .conn
+            .conn()?
             .prepare("SELECT name, color, is_system FROM tag_master ORDER BY name")?;
 
         let tags = stmt
             .query_map([], |row| {
                 Ok((row.get(0)?, row.get(1)?, row.get::<_, i32>(2)? == 1))
             })?
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<rusqlite::Result<Vec<_>>>()?;
 
         Ok(tags)
     }
 
     pub fn create_custom_tag(&self, name: &str, color: Option<&str>) -> Result<()> {
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT INTO tag_master (name, color, is_system) VALUES (?1, ?2, 0)",
             (name, color),
         )?;
@@ -284,7 +493,7 @@ impl Database {
     }
 
     pub fn update_tag_color(&self, name: &str, color: &str) -> Result<()> {
-        self.conn.execute(
+        self.conn()?.execute(
             "UPDATE tag_master SET color = ?1 WHERE name = ?2",
             (color, name),
         )?;
@@ -293,7 +502,7 @@ impl Database {
 
     pub fn delete_custom_tag(&self, name: &str) -> Result<()> {
         // システムタグは削除できない
-        self.conn.execute(
+        self.conn()?.execute(
             "DELETE FROM tag_master WHERE name = ?1 AND is_system = 0",
             [name],
         )?;
@@ -301,111 +510,465 @@ impl Database {
     }
 
     // 正規表現検索
-    pub fn search_items(&self, pattern: &str, use_regex: bool) -> Result<Vec<ClipboardItem>> {
+    // include_sensitive が false の場合、機密情報を含むアイテムは結果から除外する
+    pub fn search_items(
+        &self,
+        pattern: &str,
+        use_regex: bool,
+        include_sensitive: bool,
+        source: Option<&str>,
+    ) -> Result<Vec<ClipboardItem>> {
         if use_regex {
             // 正規表現検索
-            let regex = match Regex::new(pattern) {
-                Ok(r) => r,
-                Err(_) => return Ok(vec![]), // 無効な正規表現の場合は空の結果を返す
-            };
+            // 無効な正規表現はエラーにする
+            let regex = Regex::new(pattern)?;
 
             let all_items = self.get_all_items()?;
             let filtered_items: Vec<ClipboardItem> = all_items
                 .into_iter()
+                .filter(|item| include_sensitive || !item.is_sensitive())
+                .filter(|item| source.is_none_or(|s| item.application_source.as_deref() == Some(s)))
                 .filter(|item| regex.is_match(&item.content))
                 .collect();
 
             Ok(filtered_items)
         } else {
             // 通常の検索（LIKE演算子）
-            let mut stmt = self.conn.prepare(
-                "SELECT id, content, item_type, timestamp, is_pinned, application_source 
-                 FROM clipboard_items 
-                 WHERE content LIKE ?1
-                 ORDER BY is_pinned DESC, timestamp DESC",
-            )?;
-
             let search_pattern = format!("%{pattern}%");
-            let items = stmt
-                .query_map([search_pattern], |row| {
-                    let id: String = row.get(0)?;
-                    let content: String = row.get(1)?;
-                    let item_type_str: String = row.get(2)?;
-                    let timestamp_str: String = row.get(3)?;
-                    let is_pinned: bool = row.get(4)?;
-                    let application_source: Option<String> = row.get(5)?;
-
-                    let item_type = match item_type_str.as_str() {
-                        "image" => ClipboardItemType::Image,
-                        "file" => ClipboardItemType::File,
-                        _ => ClipboardItemType::Text,
-                    };
-
-                    let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-                        .unwrap()
-                        .with_timezone(&Utc);
-
-                    let tags = self.get_tags_for_item(&id).unwrap_or_default();
-
-                    Ok(ClipboardItem {
-                        id,
-                        content,
-                        item_type,
-                        timestamp,
-                        is_pinned,
-                        tags,
-                        application_source,
-                    })
-                })?
-                .collect::<Result<Vec<_>>>()?;
-
-            Ok(items)
+            self.load_items(
+                &format!(
+                    "SELECT {ITEM_COLUMNS}
+                     FROM clipboard_items c
+                     WHERE c.content LIKE ?1
+                       AND (?2 OR NOT EXISTS (
+                           SELECT 1 FROM tags WHERE tags.item_id = c.id AND tags.tag = ?3
+                       ))
+                       AND (?4 IS NULL OR c.application_source = ?4)
+                     ORDER BY c.is_pinned DESC, c.timestamp DESC"
+                ),
+                (search_pattern, include_sensitive, SENSITIVE_TAG, source),
+            )
         }
     }
 
+    // コピー元アプリケーションによるフィルタリング
+    pub fn get_items_by_source(&self, source: &str) -> Result<Vec<ClipboardItem>> {
+        let items = self.load_items(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 WHERE c.application_source = ?1
+                 ORDER BY c.is_pinned DESC, c.timestamp DESC"
+            ),
+            [source],
+        )?;
+
+        // 機密情報は伏せ字にして返す
+        Ok(items.into_iter().map(ClipboardItem::masked).collect())
+    }
+
+    /// 履歴に含まれるコピー元アプリケーションとアイテム数
+    pub fn get_application_sources(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn()?.prepare(
+            "SELECT application_source, COUNT(*)
+             FROM clipboard_items
+             WHERE application_source IS NOT NULL
+             GROUP BY application_source
+             ORDER BY COUNT(*) DESC, application_source",
+        )?;
+
+        let sources = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(sources)
+    }
+
     // タグによるフィルタリング
     pub fn get_items_by_tag(&self, tag: &str) -> Result<Vec<ClipboardItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT DISTINCT c.id, c.content, c.item_type, c.timestamp, c.is_pinned, c.application_source 
-             FROM clipboard_items c
-             JOIN tags t ON c.id = t.item_id
-             WHERE t.tag = ?1
-             ORDER BY c.is_pinned DESC, c.timestamp DESC"
+        let items = self.load_items(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 WHERE EXISTS (SELECT 1 FROM tags t WHERE t.item_id = c.id AND t.tag = ?1)
+                 ORDER BY c.is_pinned DESC, c.timestamp DESC"
+            ),
+            [tag],
         )?;
 
-        let items = stmt
-            .query_map([tag], |row| {
-                let id: String = row.get(0)?;
-                let content: String = row.get(1)?;
-                let item_type_str: String = row.get(2)?;
-                let timestamp_str: String = row.get(3)?;
-                let is_pinned: bool = row.get(4)?;
-                let application_source: Option<String> = row.get(5)?;
-
-                let item_type = match item_type_str.as_str() {
-                    "image" => ClipboardItemType::Image,
-                    "file" => ClipboardItemType::File,
-                    _ => ClipboardItemType::Text,
-                };
-
-                let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-                    .unwrap()
-                    .with_timezone(&Utc);
-
-                let tags = self.get_tags_for_item(&id).unwrap_or_default();
-
-                Ok(ClipboardItem {
-                    id,
-                    content,
-                    item_type,
-                    timestamp,
-                    is_pinned,
-                    tags,
-                    application_source,
-                })
-            })?
-            .collect::<Result<Vec<_>>>()?;
+        // 機密情報は伏せ字にして返す
+        Ok(items.into_iter().map(ClipboardItem::masked).collect())
+    }
+
+    // 全文検索（関連度順）
+    // use_regex が true の場合は従来どおり正規表現でフィルタリングする
+    pub fn search_ranked(
+        &self,
+        query: &str,
+        use_regex: bool,
+        include_sensitive: bool,
+        limit: i64,
+    ) -> Result<Vec<SearchMatch>> {
+        if use_regex {
+            // 無効な正規表現はエラーにする
+            let regex = Regex::new(query)?;
+
+            let matches = self
+                .get_all_items()?
+                .into_iter()
+                .filter(|item| include_sensitive || !item.is_sensitive())
+                .filter_map(|item| regex_match(item, &regex))
+                .take(limit.max(0) as usize)
+                .collect();
+
+            return Ok(matches);
+        }
+
+        let Some(match_query) = fts::build_match_query(query) else {
+            return Ok(vec![]);
+        };
+
+        let loaded = self.load_items_with(
+            &format!(
+                "SELECT {ITEM_COLUMNS},
+                        bm25(clipboard_fts),
+                        highlight(clipboard_fts, 0, ?2, ?3),
+                        snippet(clipboard_fts, 0, ?2, ?3, '…', 16)
+                 FROM clipboard_fts
+                 JOIN clipboard_items c ON c.rowid = clipboard_fts.rowid
+                 WHERE clipboard_fts MATCH ?1
+                   AND (?5 OR NOT EXISTS (
+                       SELECT 1 FROM tags WHERE tags.item_id = c.id AND tags.tag = ?6
+                   ))
+                 ORDER BY bm25(clipboard_fts), c.timestamp DESC
+                 LIMIT ?4"
+            ),
+            (
+                match_query,
+                fts::MATCH_START.to_string(),
+                fts::MATCH_END.to_string(),
+                limit,
+                include_sensitive,
+                SENSITIVE_TAG,
+            ),
+            |row| {
+                let rank: f64 = row.get(ITEM_COLUMN_COUNT)?;
+                let highlighted: String = row.get(ITEM_COLUMN_COUNT + 1)?;
+                let snippet: String = row.get(ITEM_COLUMN_COUNT + 2)?;
+                Ok((rank, highlighted, snippet))
+            },
+        )?;
+
+        let matches = loaded
+            .rows
+            .into_iter()
+            .map(|(item, (rank, highlighted, snippet))| {
+                let (_, highlights) = fts::parse_highlighted(&highlighted);
+                let (snippet, snippet_highlights) = fts::parse_highlighted(&snippet);
+
+                SearchMatch {
+                    item,
+                    rank,
+                    snippet,
+                    snippet_highlights,
+                    highlights,
+                }
+            })
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+// 正規表現に一致した箇所から SearchMatch を作成する
+fn regex_match(item: ClipboardItem, regex: &Regex) -> Option<SearchMatch> {
+    // バイト位置を文字位置に変換する
+    let char_index = |byte: usize| item.content[..byte].chars().count();
+
+    let highlights: Vec<(usize, usize)> = regex
+        .find_iter(&item.content)
+        .filter(|m| !m.is_empty())
+        .map(|m| (char_index(m.start()), char_index(m.end())))
+        .collect();
+
+    let &(first_start, first_end) = highlights.first()?;
+
+    // 最初の一致箇所の前後を抜き出してスニペットにする
+    const CONTEXT: usize = 32;
+    let snippet_start = first_start.saturating_sub(CONTEXT);
+    let snippet_end = first_end + CONTEXT;
+    let total = item.content.chars().count();
+
+    let mut snippet = String::new();
+    if snippet_start > 0 {
+        snippet.push('…');
+    }
+    let offset = snippet.chars().count();
+    snippet.extend(
+        item.content
+            .chars()
+            .skip(snippet_start)
+            .take(snippet_end - snippet_start),
+    );
+    if snippet_end < total {
+        snippet.push('…');
+    }
+
+    let snippet_highlights = highlights
+        .iter()
+        .filter(|(s, e)| *s >= snippet_start && *e <= snippet_end)
+        .map(|(s, e)| (s - snippet_start + offset, e - snippet_start + offset))
+        .collect();
+
+    Some(SearchMatch {
+        item,
+        rank: 0.0,
+        snippet,
+        snippet_highlights,
+        highlights,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ClipboardItemType;
+
+    fn memory_db() -> Database {
+        Database::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn insert_text(db: &Database, content: &str) -> ClipboardItem {
+        let item = ClipboardItem::new(content.to_string(), ClipboardItemType::Text);
+        db.insert_item(&item).unwrap();
+        item
+    }
+
+    fn search(db: &Database, query: &str) -> Vec<String> {
+        db.search_ranked(query, false, false, 50)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.item.content)
+            .collect()
+    }
+
+    #[test]
+    fn test_full_text_search_queries() {
+        let db = memory_db();
+        insert_text(&db, "the quick brown fox");
+        insert_text(&db, "clipboard history manager");
+        insert_text(&db, "brown bear");
+
+        assert_eq!(search(&db, "fox"), vec!["the quick brown fox"]);
+        assert_eq!(search(&db, "clip*"), vec!["clipboard history manager"]);
+        assert_eq!(search(&db, "\"quick brown\""), vec!["the quick brown fox"]);
+        assert!(search(&db, "\"brown quick\"").is_empty());
+        assert_eq!(search(&db, "brown NOT fox"), vec!["brown bear"]);
+        assert_eq!(search(&db, "fox OR bear").len(), 2);
+        assert!(search(&db, "\"").is_empty());
+    }
+
+    #[test]
+    fn test_full_text_index_follows_changes() {
+        let db = memory_db();
+        let item = insert_text(&db, "temporary note");
+        assert_eq!(search(&db, "temporary").len(), 1);
+
+        db.conn()
+            .unwrap()
+            .execute(
+                "UPDATE clipboard_items SET content = 'permanent note' WHERE id = ?1",
+                [&item.id],
+            )
+            .unwrap();
+        assert!(search(&db, "temporary").is_empty());
+        assert_eq!(search(&db, "permanent").len(), 1);
+
+        db.delete_item(&item.id).unwrap();
+        assert!(search(&db, "permanent").is_empty());
+    }
+
+    #[test]
+    fn test_search_highlights() {
+        let db = memory_db();
+        insert_text(&db, "say hello to hello");
+
+        let matches = db.search_ranked("hello", false, false, 10).unwrap();
+        assert_eq!(matches[0].highlights, vec![(4, 9), (13, 18)]);
+        assert_eq!(matches[0].snippet, "say hello to hello");
+
+        let matches = db.search_ranked("h.l+o", true, false, 10).unwrap();
+        assert_eq!(matches[0].highlights, vec![(4, 9), (13, 18)]);
+        assert_eq!(matches[0].snippet_highlights, vec![(4, 9), (13, 18)]);
+
+        // 無効な正規表現は空の結果ではなくエラーになる
+        assert!(matches!(
+            db.search_ranked("(hello", true, false, 10),
+            Err(ClipediaError::InvalidRegex(_))
+        ));
+        assert!(matches!(
+            db.search_items("[", true, true, None),
+            Err(ClipediaError::InvalidRegex(_))
+        ));
+    }
+
+    #[test]
+    fn test_formats_are_stored_and_removed_with_item() {
+        let db = memory_db();
+        let item = ClipboardItem::new("bold".to_string(), ClipboardItemType::Text);
+        let html = FormatData {
+            format: ClipboardFormat::Html,
+            data: "<b>bold</b>".to_string(),
+        };
+        db.insert_text_item(&item, &[html]).unwrap();
+
+        let formats = db.get_formats(&item.id).unwrap();
+        assert_eq!(formats.len(), 1);
+        assert_eq!(formats[0].format, ClipboardFormat::Html);
+        assert_eq!(formats[0].data, "<b>bold</b>");
+
+        db.delete_item(&item.id).unwrap();
+        assert!(db.get_formats(&item.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sensitive_items() {
+        let db = memory_db();
+        let mut secret = ClipboardItem::new(
+            "password Xk9ApQ2mZ7rT4vW8yB3nL6sD1fH5jK0c".to_string(),
+            ClipboardItemType::Text,
+        );
+        secret.tags.push(SENSITIVE_TAG.to_string());
+        db.insert_item(&secret).unwrap();
+        insert_text(&db, "password reminder");
+
+        // 検索結果からはデフォルトで除外される
+        assert_eq!(search(&db, "password"), vec!["password reminder"]);
+        assert_eq!(
+            db.search_items("password", false, false, None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.search_items("password", false, true, None)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            db.search_items("pass.*", true, false, None).unwrap().len(),
+            1
+        );
+
+        // 一覧では伏せ字になる
+        let items = db.get_items_paginated(0, 10, ItemOrder::Newest).unwrap();
+        let masked = items.iter().find(|i| i.id == secret.id).unwrap();
+        assert!(!masked.content.contains("Xk9ApQ2m"));
+
+        // 保存期間を過ぎたものだけ削除される
+        assert!(db
+            .delete_expired_sensitive(chrono::Duration::hours(1))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.delete_expired_sensitive(chrono::Duration::seconds(-1))
+                .unwrap(),
+            vec![secret.id.clone()]
+        );
+        assert_eq!(db.get_total_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_items_by_sensitive_tag_are_masked() {
+        let db = memory_db();
+        let mut secret = ClipboardItem::new(
+            "token Xk9ApQ2mZ7rT4vW8yB3nL6sD1fH5jK0c".to_string(),
+            ClipboardItemType::Text,
+        );
+        secret.tags.push(SENSITIVE_TAG.to_string());
+        db.insert_item(&secret).unwrap();
+
+        let items = db.get_items_by_tag(SENSITIVE_TAG).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(!items[0].content.contains("Xk9ApQ2m"));
+    }
+
+    #[test]
+    fn test_repeated_copies_are_merged() {
+        let db = memory_db();
+        let first = insert_text(&db, "repeated");
+        insert_text(&db, "other");
+        insert_text(&db, "other");
+        let again = ClipboardItem::new("repeated".to_string(), ClipboardItemType::Text);
+        assert_eq!(
+            db.insert_item(&again).unwrap(),
+            InsertOutcome::Merged(first.id.clone())
+        );
+        assert_eq!(db.get_total_count().unwrap(), 2);
+
+        let item = db.get_item(&first.id).unwrap().unwrap();
+        assert_eq!(item.copy_count, 2);
+        assert!(item.last_used_at.is_some());
+        assert_eq!(item.timestamp, again.timestamp);
+
+        // 履歴からのコピーも数える
+        assert_eq!(
+            db.record_copy(&content_hash("text", "repeated")).unwrap(),
+            Some(first.id.clone())
+        );
+        assert!(db
+            .record_copy(&content_hash("text", "missing"))
+            .unwrap()
+            .is_none());
+
+        let order = |order| -> Vec<(String, i64)> {
+            db.get_items_paginated(0, 10, order)
+                .unwrap()
+                .into_iter()
+                .map(|item| (item.content, item.copy_count))
+                .collect()
+        };
+        assert_eq!(
+            order(ItemOrder::MostUsed),
+            vec![("repeated".to_string(), 3), ("other".to_string(), 2)]
+        );
+        assert_eq!(order(ItemOrder::Oldest)[0].0, "other");
+    }
+
+    #[test]
+    fn test_filter_by_source() {
+        let db = memory_db();
+        for (content, source) in [
+            ("from firefox", Some("firefox")),
+            ("also firefox", Some("firefox")),
+            ("from terminal", Some("Gnome-terminal")),
+            ("unknown", None),
+        ] {
+            let mut item = ClipboardItem::new(content.to_string(), ClipboardItemType::Text);
+            item.application_source = source.map(str::to_string);
+            db.insert_item(&item).unwrap();
+        }
 
-        Ok(items)
+        assert_eq!(db.get_items_by_source("firefox").unwrap().len(), 2);
+        assert_eq!(
+            db.search_items("from", false, false, Some("firefox"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.search_items("from", true, false, Some("Gnome-terminal"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.get_application_sources().unwrap(),
+            vec![
+                ("firefox".to_string(), 2),
+                ("Gnome-terminal".to_string(), 1)
+            ]
+        );
     }
 }
@@ -0,0 +1,736 @@
+use super::items::{MalformedRow, ITEM_COLUMNS};
+use super::Database;
+use crate::content_analyzer::SENSITIVE_TAG;
+use crate::error::Result;
+use crate::models::{ClipboardItem, ClipboardItemType, ItemOrder};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+// 1 ページに返すアイテムの最大数
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// query_items の条件。指定した条件をすべて満たすアイテムを返す
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ItemFilter {
+    // 内容に含まれる文字列（use_regex が true の場合は正規表現）
+    pub text: Option<String>,
+    pub use_regex: bool,
+    // いずれかのタグが付いている
+    pub any_tags: Vec<String>,
+    // すべてのタグが付いている
+    pub all_tags: Vec<String>,
+    // いずれのタグも付いていない
+    pub exclude_tags: Vec<String>,
+    // 空の場合はすべての種類
+    pub item_types: Vec<ClipboardItemType>,
+    pub pinned_only: bool,
+    pub source: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    // 内容の文字数
+    pub min_length: Option<i64>,
+    pub max_length: Option<i64>,
+    // 機密情報のアイテムも内容（文字列・文字数）の条件の対象にする
+    pub include_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ItemQuery {
+    pub filter: ItemFilter,
+    pub order: ItemOrder,
+    // 前のページの next_cursor。省略した場合は最初のページ
+    pub cursor: Option<ItemCursor>,
+    pub limit: i64,
+}
+
+impl Default for ItemQuery {
+    fn default() -> Self {
+        Self {
+            filter: ItemFilter::default(),
+            order: ItemOrder::default(),
+            cursor: None,
+            limit: 50,
+        }
+    }
+}
+
+/// ページの最後のアイテムの並び替えのキー。次のページはこのアイテムの次から始まるため、
+/// 途中でアイテムが追加・削除されても重複や抜けが起きない
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemCursor {
+    pub is_pinned: bool,
+    pub timestamp: DateTime<Utc>,
+    pub copy_count: i64,
+    pub id: String,
+}
+
+impl ItemCursor {
+    pub fn of(item: &ClipboardItem) -> Self {
+        Self {
+            is_pinned: item.is_pinned,
+            timestamp: item.timestamp,
+            copy_count: item.copy_count,
+            id: item.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPage {
+    pub items: Vec<ClipboardItem>,
+    // カーソルに関係なく条件に一致するアイテムの数
+    pub total_count: i64,
+    // 続きがない場合は None
+    pub next_cursor: Option<ItemCursor>,
+    // このページの範囲で読み込めずに飛ばした行
+    pub malformed: Vec<MalformedRow>,
+}
+
+/// WHERE 句の条件と、その中の ? に順に渡すパラメーター
+#[derive(Debug, Clone, Default)]
+struct Conditions {
+    clauses: Vec<String>,
+    params: Vec<Value>,
+}
+
+impl Conditions {
+    fn push(&mut self, clause: impl Into<String>, params: impl IntoIterator<Item = Value>) {
+        self.clauses.push(clause.into());
+        self.params.extend(params);
+    }
+
+    fn where_clause(&self) -> String {
+        if self.clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", self.clauses.join(" AND "))
+        }
+    }
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+// LIKE の % と _ をそのままの文字として扱う
+pub(super) fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn values<'a>(tags: &'a [String]) -> impl Iterator<Item = Value> + 'a {
+    tags.iter().map(|tag| Value::from(tag.clone()))
+}
+
+impl ItemFilter {
+    fn conditions(&self) -> Result<Conditions> {
+        let mut conditions = Conditions::default();
+        let text = self.text.as_deref().filter(|text| !text.is_empty());
+
+        if let Some(text) = text {
+            if self.use_regex {
+                // 無効な正規表現は SQL を実行する前にエラーにする
+                Regex::new(text)?;
+                conditions.push("regexp(?, c.content)", [Value::from(text.to_string())]);
+            } else {
+                conditions.push(
+                    r"c.content LIKE ? ESCAPE '\'",
+                    [Value::from(format!("%{}%", escape_like(text)))],
+                );
+            }
+        }
+        if let Some(min) = self.min_length {
+            conditions.push("length(c.content) >= ?", [Value::from(min)]);
+        }
+        if let Some(max) = self.max_length {
+            conditions.push("length(c.content) <= ?", [Value::from(max)]);
+        }
+        // 伏せ字にしている内容が条件から推測されないようにする
+        let matches_content =
+            text.is_some() || self.min_length.is_some() || self.max_length.is_some();
+        if matches_content && !self.include_sensitive {
+            conditions.push(
+                "NOT EXISTS (SELECT 1 FROM tags t WHERE t.item_id = c.id AND t.tag = ?)",
+                [Value::from(SENSITIVE_TAG.to_string())],
+            );
+        }
+
+        if !self.any_tags.is_empty() {
+            conditions.push(
+                format!(
+                    "EXISTS (SELECT 1 FROM tags t WHERE t.item_id = c.id AND t.tag IN ({}))",
+                    placeholders(self.any_tags.len())
+                ),
+                values(&self.any_tags),
+            );
+        }
+        if !self.all_tags.is_empty() {
+            let mut tags = self.all_tags.clone();
+            tags.sort();
+            tags.dedup();
+            conditions.push(
+                format!(
+                    "(SELECT COUNT(DISTINCT t.tag) FROM tags t WHERE t.item_id = c.id AND t.tag IN ({})) = {}",
+                    placeholders(tags.len()),
+                    tags.len()
+                ),
+                values(&tags),
+            );
+        }
+        if !self.exclude_tags.is_empty() {
+            conditions.push(
+                format!(
+                    "NOT EXISTS (SELECT 1 FROM tags t WHERE t.item_id = c.id AND t.tag IN ({}))",
+                    placeholders(self.exclude_tags.len())
+                ),
+                values(&self.exclude_tags),
+            );
+        }
+
+        if !self.item_types.is_empty() {
+            conditions.push(
+                format!("c.item_type IN ({})", placeholders(self.item_types.len())),
+                self.item_types
+                    .iter()
+                    .map(|item_type| Value::from(item_type.as_str().to_string())),
+            );
+        }
+        if self.pinned_only {
+            conditions.push("c.is_pinned = 1", []);
+        }
+        if let Some(source) = &self.source {
+            conditions.push("c.application_source = ?", [Value::from(source.clone())]);
+        }
+        if let Some(since) = self.since {
+            conditions.push("c.timestamp >= ?", [Value::from(since.to_rfc3339())]);
+        }
+        if let Some(until) = self.until {
+            conditions.push("c.timestamp <= ?", [Value::from(until.to_rfc3339())]);
+        }
+
+        Ok(conditions)
+    }
+}
+
+/// 並び替えのキー
+#[derive(Debug, Clone, Copy)]
+enum SortKey {
+    Pinned,
+    Timestamp,
+    CopyCount,
+    Id,
+}
+
+impl SortKey {
+    fn column(self) -> &'static str {
+        match self {
+            SortKey::Pinned => "c.is_pinned",
+            SortKey::Timestamp => "c.timestamp",
+            SortKey::CopyCount => "c.copy_count",
+            SortKey::Id => "c.id",
+        }
+    }
+
+    fn value(self, cursor: &ItemCursor) -> Value {
+        match self {
+            SortKey::Pinned => Value::from(cursor.is_pinned),
+            SortKey::Timestamp => Value::from(cursor.timestamp.to_rfc3339()),
+            SortKey::CopyCount => Value::from(cursor.copy_count),
+            SortKey::Id => Value::from(cursor.id.clone()),
+        }
+    }
+
+    /// 並び順のキーと降順かどうか。ピン留めされたアイテムを先頭にし、最後に ID で順序を一意にする
+    fn for_order(order: ItemOrder) -> &'static [(SortKey, bool)] {
+        match order {
+            ItemOrder::Newest => &[
+                (SortKey::Pinned, true),
+                (SortKey::Timestamp, true),
+                (SortKey::Id, true),
+            ],
+            ItemOrder::Oldest => &[
+                (SortKey::Pinned, true),
+                (SortKey::Timestamp, false),
+                (SortKey::Id, false),
+            ],
+            ItemOrder::MostUsed => &[
+                (SortKey::Pinned, true),
+                (SortKey::CopyCount, true),
+                (SortKey::Timestamp, true),
+                (SortKey::Id, true),
+            ],
+        }
+    }
+
+    fn order_by(keys: &[(SortKey, bool)]) -> String {
+        keys.iter()
+            .map(|(key, descending)| {
+                format!(
+                    "{} {}",
+                    key.column(),
+                    if *descending { "DESC" } else { "ASC" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// keys の順で cursor より後に並ぶアイテムの条件
+    fn after(keys: &[(SortKey, bool)], cursor: &ItemCursor) -> (String, Vec<Value>) {
+        // すべて同じ向きの場合は行値の比較にする（インデックスで範囲を絞り込める）
+        if keys.iter().all(|(_, descending)| *descending == keys[0].1) {
+            let columns: Vec<_> = keys.iter().map(|(key, _)| key.column()).collect();
+            let op = if keys[0].1 { "<" } else { ">" };
+            return (
+                format!(
+                    "({}) {op} ({})",
+                    columns.join(", "),
+                    placeholders(keys.len())
+                ),
+                keys.iter().map(|(key, _)| key.value(cursor)).collect(),
+            );
+        }
+
+        // 向きが混在する場合は a < ? OR (a = ? AND (b > ? OR (b = ? AND c > ?))) の形にする
+        let mut clause = String::new();
+        let mut params = Vec::new();
+        for (key, descending) in keys.iter().rev() {
+            let column = key.column();
+            let op = if *descending { "<" } else { ">" };
+            let value = key.value(cursor);
+            if clause.is_empty() {
+                clause = format!("{column} {op} ?");
+                params = vec![value];
+            } else {
+                clause = format!("({column} {op} ? OR ({column} = ? AND {clause}))");
+                params = [vec![value.clone(), value], params].concat();
+            }
+        }
+        (clause, params)
+    }
+}
+
+// regexp(pattern, text)。コンパイルした正規表現はクエリの実行中キャッシュされる
+pub(super) fn register_functions(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let regex = ctx.get_or_create_aux(0, |pattern| -> rusqlite::Result<Regex> {
+                Regex::new(pattern.as_str()?)
+                    .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))
+            })?;
+            // テキストでない内容（壊れた行）は一致しないものとして扱う
+            Ok(ctx
+                .get_raw(1)
+                .as_str()
+                .is_ok_and(|text| regex.is_match(text)))
+        },
+    )
+}
+
+impl Database {
+    /// 条件に一致するアイテムを 1 ページ分返す。機密情報は伏せ字にする
+    pub fn query_items(&self, query: &ItemQuery) -> Result<ItemPage> {
+        let mut conditions = query.filter.conditions()?;
+
+        let total_count = self.conn()?.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM clipboard_items c {}",
+                conditions.where_clause()
+            ),
+            rusqlite::params_from_iter(&conditions.params),
+            |row| row.get(0),
+        )?;
+
+        let keys = SortKey::for_order(query.order);
+        if let Some(cursor) = &query.cursor {
+            let (clause, params) = SortKey::after(keys, cursor);
+            conditions.push(clause, params);
+        }
+
+        // 続きがあるかどうかを判定するため 1 件多く取得する
+        let limit = query.limit.clamp(1, MAX_PAGE_SIZE);
+        let loaded = self.load_items_with(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 {}
+                 ORDER BY {}
+                 LIMIT {}",
+                conditions.where_clause(),
+                SortKey::order_by(keys),
+                limit + 1
+            ),
+            rusqlite::params_from_iter(conditions.params),
+            |_| Ok(()),
+        )?;
+        let mut items: Vec<_> = loaded.rows.into_iter().map(|(item, ())| item).collect();
+
+        // 読み込めなかった行も取得した件数に含める（途中で一覧が途切れないように）
+        let next_cursor = if (items.len() + loaded.malformed.len()) as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(ItemCursor::of)
+        } else {
+            None
+        };
+
+        Ok(ItemPage {
+            items: items.into_iter().map(ClipboardItem::masked).collect(),
+            total_count,
+            next_cursor,
+            malformed: loaded.malformed,
+        })
+    }
+
+    /// cursor の時刻より後にコピーされたアイテムを新しい順に返す。
+    /// 一覧の読み込み後に追加されたアイテムを先頭に挿入するために使う（統合されて時刻が更新されたアイテムも含む）
+    pub fn get_items_since(&self, cursor: &ItemCursor, limit: i64) -> Result<Vec<ClipboardItem>> {
+        let items = self.load_items(
+            &format!(
+                "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_items c
+                 WHERE (c.timestamp, c.id) > (?1, ?2)
+                 ORDER BY c.timestamp DESC, c.id DESC
+                 LIMIT ?3"
+            ),
+            (
+                cursor.timestamp.to_rfc3339(),
+                &cursor.id,
+                limit.clamp(1, MAX_PAGE_SIZE),
+            ),
+        )?;
+        Ok(items.into_iter().map(ClipboardItem::masked).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ClipediaError;
+    use chrono::Duration;
+
+    fn memory_db() -> Database {
+        Database::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    // minutes_ago 分前にコピーしたテキストアイテムを保存する
+    fn insert(db: &Database, content: &str, tags: &[&str], minutes_ago: i64) -> ClipboardItem {
+        let mut item = ClipboardItem::new(content.to_string(), ClipboardItemType::Text);
+        item.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        item.timestamp = Utc::now() - Duration::minutes(minutes_ago);
+        db.insert_item(&item).unwrap();
+        item
+    }
+
+    fn contents(db: &Database, filter: ItemFilter) -> Vec<String> {
+        let page = db
+            .query_items(&ItemQuery {
+                filter,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(page.total_count, page.items.len() as i64);
+        page.items.into_iter().map(|item| item.content).collect()
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_combines_filters() {
+        let db = memory_db();
+        insert(&db, "fn main() {}", &["code", "work"], 1);
+        insert(&db, "meeting notes", &["work"], 2);
+        insert(&db, "100% done_", &["todo"], 3);
+        let pinned = insert(&db, "https://example.com", &["url", "work"], 4);
+        db.update_pin_status(&pinned.id, true).unwrap();
+
+        let filter = |f: fn(&mut ItemFilter)| {
+            let mut filter = ItemFilter::default();
+            f(&mut filter);
+            filter
+        };
+
+        assert_eq!(
+            contents(&db, filter(|f| f.any_tags = tags(&["code", "todo"]))),
+            vec!["fn main() {}", "100% done_"]
+        );
+        assert_eq!(
+            contents(&db, filter(|f| f.all_tags = tags(&["work", "url"]))),
+            vec!["https://example.com"]
+        );
+        assert_eq!(
+            contents(
+                &db,
+                filter(|f| {
+                    f.any_tags = tags(&["work"]);
+                    f.exclude_tags = tags(&["code"]);
+                })
+            ),
+            vec!["https://example.com", "meeting notes"]
+        );
+        // % と _ は通常の文字として検索する
+        assert_eq!(
+            contents(&db, filter(|f| f.text = Some("0% done_".to_string()))),
+            vec!["100% done_"]
+        );
+        assert_eq!(
+            contents(
+                &db,
+                filter(|f| {
+                    f.text = Some(r"^\w+ \w+$".to_string());
+                    f.use_regex = true;
+                })
+            ),
+            vec!["meeting notes"]
+        );
+        assert_eq!(
+            contents(
+                &db,
+                filter(|f| {
+                    f.min_length = Some(11);
+                    f.max_length = Some(13);
+                })
+            ),
+            vec!["fn main() {}", "meeting notes"]
+        );
+        assert_eq!(
+            contents(&db, filter(|f| f.pinned_only = true)),
+            vec!["https://example.com"]
+        );
+        assert_eq!(
+            contents(
+                &db,
+                filter(|f| {
+                    f.since = Some(Utc::now() - Duration::seconds(150));
+                    f.until = Some(Utc::now());
+                })
+            ),
+            vec!["fn main() {}", "meeting notes"]
+        );
+        assert!(contents(
+            &db,
+            filter(|f| f.item_types = vec![ClipboardItemType::Image])
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_sensitive_items_are_masked_and_not_searchable() {
+        let db = memory_db();
+        insert(&db, "password=hunter2", &[SENSITIVE_TAG], 1);
+
+        let page = db.query_items(&ItemQuery::default()).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_ne!(page.items[0].content, "password=hunter2");
+
+        let mut filter = ItemFilter {
+            text: Some("hunter2".to_string()),
+            ..Default::default()
+        };
+        assert!(contents(&db, filter.clone()).is_empty());
+        filter.include_sensitive = true;
+        assert_eq!(contents(&db, filter).len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let db = memory_db();
+        let query = ItemQuery {
+            filter: ItemFilter {
+                text: Some("(".to_string()),
+                use_regex: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.query_items(&query),
+            Err(ClipediaError::InvalidRegex(_))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_pagination() {
+        let db = memory_db();
+        let timestamp = Utc::now();
+        // 同じ時刻のアイテムも ID で順序が決まる
+        for i in 0..7 {
+            let mut item = ClipboardItem::new(format!("item {i}"), ClipboardItemType::Text);
+            item.timestamp = timestamp - Duration::minutes(i / 2);
+            item.copy_count = i % 3;
+            db.insert_item(&item).unwrap();
+        }
+
+        for order in [ItemOrder::Newest, ItemOrder::Oldest, ItemOrder::MostUsed] {
+            let mut query = ItemQuery {
+                order,
+                limit: 3,
+                ..Default::default()
+            };
+            let mut paged = Vec::new();
+            loop {
+                let page = db.query_items(&query).unwrap();
+                assert_eq!(page.total_count, 7);
+                paged.extend(page.items.into_iter().map(|item| item.id));
+                match page.next_cursor {
+                    Some(cursor) => query.cursor = Some(cursor),
+                    None => break,
+                }
+            }
+
+            let all = db
+                .query_items(&ItemQuery {
+                    order,
+                    limit: 100,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert!(all.next_cursor.is_none());
+            let expected: Vec<_> = all.items.into_iter().map(|item| item.id).collect();
+            assert_eq!(paged, expected, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn test_reports_malformed_rows_without_ending_pagination() {
+        let db = memory_db();
+        let items: Vec<_> = (0..4)
+            .map(|i| insert(&db, &format!("item {i}"), &[], i))
+            .collect();
+        // 1 ページ目の続きの判定に使う行を読み込めなくする
+        db.conn()
+            .unwrap()
+            .execute(
+                "UPDATE clipboard_items SET content = X'00FF' WHERE id = ?1",
+                [&items[2].id],
+            )
+            .unwrap();
+
+        let mut query = ItemQuery {
+            limit: 2,
+            ..Default::default()
+        };
+        let first = db.query_items(&query).unwrap();
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.malformed.len(), 1);
+        assert_eq!(first.malformed[0].id.as_deref(), Some(items[2].id.as_str()));
+
+        query.cursor = first.next_cursor;
+        assert!(query.cursor.is_some());
+        let second = db.query_items(&query).unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].id, items[3].id);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_newest_order_uses_keyset_index() {
+        let db = memory_db();
+        let cursor = ItemCursor::of(&insert(&db, "item", &[], 0));
+        let keys = SortKey::for_order(ItemOrder::Newest);
+        let (clause, params) = SortKey::after(keys, &cursor);
+
+        let conn = db.conn().unwrap();
+        let mut stmt = conn
+            .prepare(&format!(
+                "EXPLAIN QUERY PLAN SELECT c.id FROM clipboard_items c WHERE {clause} ORDER BY {} LIMIT 50",
+                SortKey::order_by(keys)
+            ))
+            .unwrap();
+        let plan: Vec<String> = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+
+        // インデックスの順に読むため、並び替え用の一時 B-tree を使わない
+        assert!(
+            plan.iter()
+                .any(|step| step.contains("idx_clipboard_items_keyset")),
+            "{plan:?}"
+        );
+        assert!(
+            !plan.iter().any(|step| step.contains("TEMP B-TREE")),
+            "{plan:?}"
+        );
+    }
+
+    #[test]
+    fn test_items_since_cursor() {
+        let db = memory_db();
+        let pinned = insert(&db, "pinned", &[], 10);
+        db.update_pin_status(&pinned.id, true).unwrap();
+        let seen = insert(&db, "seen", &[], 5);
+
+        let page = db.query_items(&ItemQuery::default()).unwrap();
+        let latest = page
+            .items
+            .iter()
+            .map(ItemCursor::of)
+            .max_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+        assert_eq!(
+            latest.as_ref().map(|cursor| cursor.id.as_str()),
+            Some(seen.id.as_str())
+        );
+        let latest = latest.unwrap();
+        assert!(db.get_items_since(&latest, 50).unwrap().is_empty());
+
+        insert(&db, "new 1", &[], 2);
+        insert(&db, "new 2", &[], 1);
+        // 同じ内容を再度コピーすると時刻が更新される
+        insert(&db, "pinned", &[], 0);
+
+        let since: Vec<_> = db
+            .get_items_since(&latest, 50)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.content)
+            .collect();
+        assert_eq!(since, vec!["pinned", "new 2", "new 1"]);
+        assert_eq!(db.get_items_since(&latest, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_cursor_skips_items_added_while_paging() {
+        let db = memory_db();
+        for i in 0..4 {
+            insert(&db, &format!("item {i}"), &[], i + 1);
+        }
+
+        let mut query = ItemQuery {
+            limit: 2,
+            ..Default::default()
+        };
+        let first = db.query_items(&query).unwrap();
+        insert(&db, "new item", &[], 0);
+
+        query.cursor = first.next_cursor;
+        let second = db.query_items(&query).unwrap();
+        let contents: Vec<_> = second
+            .items
+            .iter()
+            .map(|item| item.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["item 2", "item 3"]);
+        assert_eq!(second.total_count, 5);
+        assert!(second.next_cursor.is_none());
+    }
+}
@@ -0,0 +1,218 @@
+use super::Database;
+use crate::error::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// 履歴の保存上限。ピン留めされたアイテムは対象外
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    // ピン留めされていないアイテムの最大件数
+    pub max_items: Option<u32>,
+    // この日数より古いアイテムを削除する
+    pub max_age_days: Option<u32>,
+    // 保存データの合計サイズ（バイト）の上限
+    pub max_total_bytes: Option<i64>,
+    // 削除後に VACUUM してファイルサイズを縮小する
+    pub vacuum: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_items: Some(1000),
+            max_age_days: None,
+            max_total_bytes: None,
+            vacuum: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    pub removed_ids: Vec<String>,
+    pub removed_by_count: usize,
+    pub removed_by_age: usize,
+    pub removed_by_size: usize,
+    pub vacuumed: bool,
+}
+
+impl RetentionReport {
+    pub fn is_empty(&self) -> bool {
+        self.removed_ids.is_empty()
+    }
+}
+
+// アイテム一件あたりの保存サイズ（本文・画像・書式付きテキスト）
+const ITEM_SIZE_SQL: &str = "
+    length(CAST(c.content AS BLOB))
+    + COALESCE((SELECT length(i.data) + length(i.thumbnail) FROM clipboard_images i WHERE i.item_id = c.id), 0)
+    + COALESCE((SELECT SUM(length(CAST(f.data AS BLOB))) FROM clipboard_formats f WHERE f.item_id = c.id), 0)";
+
+impl Database {
+    /// 保存上限を超えたアイテムを削除する
+    pub fn apply_retention(&self, policy: &RetentionPolicy) -> Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+        // 削除済みかどうかの判定用（removed_ids と同じ内容）
+        let mut removed: HashSet<String> = HashSet::new();
+        let tx = self.conn()?.unchecked_transaction()?;
+
+        // 古いアイテム
+        if let Some(days) = policy.max_age_days {
+            let cutoff = (Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+            let ids = tx
+                .prepare("SELECT id FROM clipboard_items WHERE is_pinned = 0 AND timestamp < ?1")?
+                .query_map([cutoff], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            report.removed_by_age = ids.len();
+            removed.extend(ids.iter().cloned());
+            report.removed_ids.extend(ids);
+        }
+
+        // 件数の上限を超えた古いアイテム
+        if let Some(max_items) = policy.max_items {
+            let ids = tx
+                .prepare(
+                    "SELECT id FROM clipboard_items
+                     WHERE is_pinned = 0
+                     ORDER BY timestamp DESC
+                     LIMIT -1 OFFSET ?1",
+                )?
+                .query_map([max_items], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|id| !removed.contains(id))
+                .collect::<Vec<_>>();
+            report.removed_by_count = ids.len();
+            removed.extend(ids.iter().cloned());
+            report.removed_ids.extend(ids);
+        }
+
+        // 合計サイズの上限を超えた分を古い順に削除
+        if let Some(max_total_bytes) = policy.max_total_bytes {
+            let sizes = tx
+                .prepare(&format!(
+                    "SELECT c.id, c.is_pinned, {ITEM_SIZE_SQL}
+                     FROM clipboard_items c
+                     ORDER BY c.timestamp ASC"
+                ))?
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, bool>(1)?,
+                        row.get::<_, i64>(2)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let mut total: i64 = sizes
+                .iter()
+                .filter(|(id, _, _)| !removed.contains(id))
+                .map(|(_, _, size)| size)
+                .sum();
+
+            for (id, is_pinned, size) in sizes {
+                if total <= max_total_bytes {
+                    break;
+                }
+                if is_pinned || removed.contains(&id) {
+                    continue;
+                }
+                total -= size;
+                report.removed_by_size += 1;
+                removed.insert(id.clone());
+                report.removed_ids.push(id);
+            }
+        }
+
+        for id in &report.removed_ids {
+            tx.execute("DELETE FROM clipboard_items WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+
+        if policy.vacuum && !report.is_empty() {
+            let conn = self.conn()?;
+            conn.execute_batch("VACUUM")?;
+            // VACUUM で rowid が変わる可能性があるため全文検索インデックスを作り直す
+            conn.execute(
+                "INSERT INTO clipboard_fts (clipboard_fts) VALUES ('rebuild')",
+                [],
+            )?;
+            report.vacuumed = true;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ClipboardItem, ClipboardItemType};
+    use rusqlite::Connection;
+
+    fn insert(db: &Database, content: &str, minutes_ago: i64, is_pinned: bool) -> String {
+        let mut item = ClipboardItem::new(content.to_string(), ClipboardItemType::Text);
+        item.timestamp = Utc::now() - chrono::Duration::minutes(minutes_ago);
+        item.is_pinned = is_pinned;
+        db.insert_item(&item).unwrap();
+        item.id
+    }
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            max_items: None,
+            max_age_days: None,
+            max_total_bytes: None,
+            vacuum: false,
+        }
+    }
+
+    #[test]
+    fn test_prune_by_count_keeps_pinned() {
+        let db = Database::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let pinned = insert(&db, "pinned", 100, true);
+        let old = insert(&db, "old", 50, false);
+        insert(&db, "new", 10, false);
+        insert(&db, "newest", 0, false);
+
+        let report = db
+            .apply_retention(&RetentionPolicy {
+                max_items: Some(2),
+                ..policy()
+            })
+            .unwrap();
+        assert_eq!(report.removed_ids, vec![old]);
+        assert_eq!(report.removed_by_count, 1);
+        assert!(db.get_item(&pinned).unwrap().is_some());
+        assert_eq!(db.get_total_count().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_prune_by_age_and_size() {
+        let db = Database::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+        insert(&db, "ancient", 60 * 24 * 10, false);
+        insert(&db, "ancient but pinned", 60 * 24 * 10, true);
+        let large = insert(&db, &"x".repeat(100), 30, false);
+        insert(&db, "small", 0, false);
+
+        let report = db
+            .apply_retention(&RetentionPolicy {
+                max_age_days: Some(7),
+                max_total_bytes: Some(50),
+                vacuum: true,
+                ..policy()
+            })
+            .unwrap();
+        assert_eq!(report.removed_by_age, 1);
+        assert_eq!(report.removed_by_size, 1);
+        assert!(report.removed_ids.contains(&large));
+        assert!(report.vacuumed);
+        assert_eq!(db.get_total_count().unwrap(), 2);
+
+        // VACUUM 後も全文検索が使える
+        assert_eq!(db.search_ranked("small", false, true, 10).unwrap().len(), 1);
+    }
+}
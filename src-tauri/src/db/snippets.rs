@@ -0,0 +1,221 @@
+use super::query::escape_like;
+use super::Database;
+use crate::error::{ClipediaError, Result};
+use crate::models::Snippet;
+use chrono::{DateTime, Utc};
+use rusqlite::{ErrorCode, OptionalExtension, Row};
+
+const SNIPPET_COLUMNS: &str = "id, name, abbreviation, content, created_at, updated_at";
+
+fn snippet_from_row(row: &Row) -> rusqlite::Result<Snippet> {
+    let parse = |index: usize| -> rusqlite::Result<DateTime<Utc>> {
+        let value: String = row.get(index)?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(|value| value.with_timezone(&Utc))
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    index,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })
+    };
+
+    Ok(Snippet {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        abbreviation: row.get(2)?,
+        content: row.get(3)?,
+        created_at: parse(4)?,
+        updated_at: parse(5)?,
+    })
+}
+
+// 略語の重複はわかりやすいエラーにする
+fn snippet_error(e: rusqlite::Error) -> ClipediaError {
+    if e.sqlite_error_code() == Some(ErrorCode::ConstraintViolation) {
+        return ClipediaError::Conflict(
+            "the abbreviation is already used by another snippet".to_string(),
+        );
+    }
+    e.into()
+}
+
+impl Database {
+    pub fn insert_snippet(&self, snippet: &Snippet) -> Result<()> {
+        self.conn()?
+            .execute(
+                "INSERT INTO snippets (id, name, abbreviation, content, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                (
+                    &snippet.id,
+                    &snippet.name,
+                    &snippet.abbreviation,
+                    &snippet.content,
+                    snippet.created_at.to_rfc3339(),
+                    snippet.updated_at.to_rfc3339(),
+                ),
+            )
+            .map_err(snippet_error)?;
+        Ok(())
+    }
+
+    /// 名前・略語・本文を更新する。該当するスニペットがなければ false を返す
+    pub fn update_snippet(&self, snippet: &Snippet) -> Result<bool> {
+        let updated = self
+            .conn()?
+            .execute(
+                "UPDATE snippets SET name = ?1, abbreviation = ?2, content = ?3, updated_at = ?4
+             WHERE id = ?5",
+                (
+                    &snippet.name,
+                    &snippet.abbreviation,
+                    &snippet.content,
+                    snippet.updated_at.to_rfc3339(),
+                    &snippet.id,
+                ),
+            )
+            .map_err(snippet_error)?;
+        Ok(updated > 0)
+    }
+
+    pub fn delete_snippet(&self, id: &str) -> Result<()> {
+        self.conn()?
+            .execute("DELETE FROM snippets WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn get_snippet(&self, id: &str) -> Result<Option<Snippet>> {
+        Ok(self
+            .conn()?
+            .query_row(
+                &format!("SELECT {SNIPPET_COLUMNS} FROM snippets WHERE id = ?1"),
+                [id],
+                snippet_from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn get_snippets(&self) -> Result<Vec<Snippet>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SNIPPET_COLUMNS} FROM snippets ORDER BY name COLLATE NOCASE"
+        ))?;
+        let snippets = stmt
+            .query_map([], snippet_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(snippets)
+    }
+
+    /// 略語・名前・本文で検索する。
+    /// 略語が完全に一致するもの、略語が前方一致するもの、名前に含むもの、本文に含むものの順に並べる
+    pub fn search_snippets(&self, query: &str, limit: i64) -> Result<Vec<Snippet>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SNIPPET_COLUMNS}
+             FROM snippets
+             WHERE abbreviation LIKE ?2 ESCAPE '\\'
+                OR name LIKE ?3 ESCAPE '\\'
+                OR content LIKE ?3 ESCAPE '\\'
+             ORDER BY CASE
+                 WHEN abbreviation = ?1 THEN 0
+                 WHEN abbreviation LIKE ?2 ESCAPE '\\' THEN 1
+                 WHEN name LIKE ?3 ESCAPE '\\' THEN 2
+                 ELSE 3
+             END, name COLLATE NOCASE
+             LIMIT ?4"
+        ))?;
+
+        let escaped = escape_like(query);
+        let prefix = format!("{escaped}%");
+        let contains = format!("%{escaped}%");
+        let snippets = stmt
+            .query_map((query, prefix, contains, limit), snippet_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(snippets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn memory_db() -> Database {
+        Database::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn names(snippets: Vec<Snippet>) -> Vec<String> {
+        snippets.into_iter().map(|snippet| snippet.name).collect()
+    }
+
+    #[test]
+    fn test_snippet_search_order_and_unique_abbreviation() {
+        let db = memory_db();
+        for (name, abbreviation, content) in [
+            ("Signature", Some("sig"), "Best regards"),
+            ("Signal note", Some("sign"), "..."),
+            ("Address", None, "see signature below"),
+            ("Greeting", Some("hi"), "Hello"),
+        ] {
+            let snippet = Snippet::new(
+                name.to_string(),
+                abbreviation.map(str::to_string),
+                content.to_string(),
+            );
+            db.insert_snippet(&snippet).unwrap();
+        }
+
+        assert_eq!(
+            names(db.search_snippets("sig", 10).unwrap()),
+            vec!["Signature", "Signal note", "Address"]
+        );
+        assert_eq!(
+            names(db.search_snippets("greet", 10).unwrap()),
+            vec!["Greeting"]
+        );
+        assert_eq!(db.search_snippets("sig", 1).unwrap().len(), 1);
+
+        // % と _ はそのままの文字として検索する
+        db.insert_snippet(&Snippet::new(
+            "Discount".to_string(),
+            None,
+            "100% off for a_b".to_string(),
+        ))
+        .unwrap();
+        assert_eq!(
+            names(db.search_snippets("100%", 10).unwrap()),
+            vec!["Discount"]
+        );
+        assert_eq!(
+            names(db.search_snippets("a_b", 10).unwrap()),
+            vec!["Discount"]
+        );
+        assert_eq!(
+            names(db.search_snippets("%", 10).unwrap()),
+            vec!["Discount"]
+        );
+        db.delete_snippet(&db.search_snippets("a_b", 1).unwrap()[0].id)
+            .unwrap();
+
+        let duplicate = Snippet::new("Other".to_string(), Some("hi".to_string()), String::new());
+        let error = db.insert_snippet(&duplicate).unwrap_err();
+        assert!(matches!(error, ClipediaError::Conflict(_)));
+
+        // 略語のないスニペットは何件でも保存できる
+        let mut snippet = db.search_snippets("Address", 1).unwrap().remove(0);
+        db.insert_snippet(&Snippet::new("Another".to_string(), None, String::new()))
+            .unwrap();
+
+        snippet.content = "updated".to_string();
+        assert!(db.update_snippet(&snippet).unwrap());
+        assert_eq!(
+            db.get_snippet(&snippet.id).unwrap().unwrap().content,
+            "updated"
+        );
+
+        db.delete_snippet(&snippet.id).unwrap();
+        assert!(db.get_snippet(&snippet.id).unwrap().is_none());
+        assert_eq!(db.get_snippets().unwrap().len(), 4);
+    }
+}
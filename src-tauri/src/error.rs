@@ -0,0 +1,125 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::sync::PoisonError;
+use thiserror::Error;
+
+pub type Result<T, E = ClipediaError> = std::result::Result<T, E>;
+
+/// データベース・クリップボード・コマンドのエラー。
+/// フロントエンドには `{ code, message, details }` として送信し、code で種類を判別できるようにする
+#[derive(Debug, Error)]
+pub enum ClipediaError {
+    #[error("{kind} not found: {id}")]
+    NotFound { kind: &'static str, id: String },
+    #[error("the database is locked")]
+    DatabaseLocked,
+    #[error("incorrect passphrase")]
+    WrongPassphrase,
+    #[error("{0}")]
+    Encryption(String),
+    #[error("invalid regex: {0}")]
+    InvalidRegex(#[from] regex::Error),
+    #[error("clipboard unavailable: {0}")]
+    ClipboardUnavailable(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    // 一意であるべき値（スニペットの略語など）の重複
+    #[error("{0}")]
+    Conflict(String),
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ClipediaError {
+    pub fn not_found(kind: &'static str, id: impl Into<String>) -> Self {
+        ClipediaError::NotFound {
+            kind,
+            id: id.into(),
+        }
+    }
+
+    pub fn clipboard(error: impl std::fmt::Display) -> Self {
+        ClipediaError::ClipboardUnavailable(error.to_string())
+    }
+
+    /// フロントエンドで判別に使うエラーコード
+    pub fn code(&self) -> &'static str {
+        match self {
+            ClipediaError::NotFound { .. } => "notFound",
+            ClipediaError::DatabaseLocked => "databaseLocked",
+            ClipediaError::WrongPassphrase => "wrongPassphrase",
+            ClipediaError::Encryption(_) => "encryption",
+            ClipediaError::InvalidRegex(_) => "invalidRegex",
+            ClipediaError::ClipboardUnavailable(_) => "clipboardUnavailable",
+            ClipediaError::InvalidInput(_) => "invalidInput",
+            ClipediaError::Conflict(_) => "conflict",
+            ClipediaError::Database(_) => "database",
+            ClipediaError::Io(_) => "io",
+            ClipediaError::Internal(_) => "internal",
+        }
+    }
+
+    // 見つからなかった ID や正規表現のエラー箇所など、メッセージとは別に扱いたい情報
+    fn details(&self) -> Option<String> {
+        match self {
+            ClipediaError::NotFound { id, .. } => Some(id.clone()),
+            ClipediaError::InvalidRegex(e) => Some(e.to_string()),
+            ClipediaError::ClipboardUnavailable(reason) => Some(reason.clone()),
+            ClipediaError::Database(e) => e.sqlite_error_code().map(|code| format!("{code:?}")),
+            ClipediaError::Io(e) => Some(format!("{:?}", e.kind())),
+            _ => None,
+        }
+    }
+}
+
+// Mutex の lock() が失敗するのは、ロックを保持したスレッドがパニックした場合のみ
+impl<T> From<PoisonError<T>> for ClipediaError {
+    fn from(e: PoisonError<T>) -> Self {
+        ClipediaError::Internal(e.to_string())
+    }
+}
+
+impl Serialize for ClipediaError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ClipediaError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_error() {
+        let error = ClipediaError::not_found("item", "abc");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "notFound",
+                "message": "item not found: abc",
+                "details": "abc",
+            })
+        );
+
+        let pattern = String::from("(");
+        let error = ClipediaError::from(regex::Regex::new(&pattern).unwrap_err());
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "invalidRegex");
+        assert!(value["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid regex:"));
+
+        let value = serde_json::to_value(ClipediaError::DatabaseLocked).unwrap();
+        assert_eq!(value["code"], "databaseLocked");
+        assert!(value["details"].is_null());
+    }
+}
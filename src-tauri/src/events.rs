@@ -0,0 +1,77 @@
+use crate::db::{Database, InsertOutcome};
+use crate::models::ClipboardItem;
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// 履歴の変更。受信側でフロントエンドにイベントとして送信し、トレイの最近のアイテムを更新する
+#[derive(Debug, Clone)]
+pub enum HistoryEvent {
+    ItemAdded(ClipboardItem),
+    // ピン留め・タグ・コピー回数などが変わった
+    ItemUpdated(ClipboardItem),
+    ItemDeleted(DeletedItems),
+    HistoryCleared,
+}
+
+/// item-deleted イベントの内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedItems {
+    pub ids: Vec<String>,
+}
+
+impl HistoryEvent {
+    /// フロントエンドに送信するイベント名
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistoryEvent::ItemAdded(_) => "item-added",
+            HistoryEvent::ItemUpdated(_) => "item-updated",
+            HistoryEvent::ItemDeleted(_) => "item-deleted",
+            HistoryEvent::HistoryCleared => "history-cleared",
+        }
+    }
+}
+
+/// 履歴の変更を通知するハンドル。監視処理とコマンドで共有する
+#[derive(Clone)]
+pub struct HistoryEvents {
+    sender: UnboundedSender<HistoryEvent>,
+}
+
+impl HistoryEvents {
+    pub fn channel() -> (Self, UnboundedReceiver<HistoryEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    // 受信側が終了していても無視する
+    pub fn send(&self, event: HistoryEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// 保存したアイテムを読み直して通知する（新しく追加された場合は item-added、統合された場合は item-updated）
+    pub fn inserted(&self, db: &Database, outcome: &InsertOutcome, inserted_id: &str) {
+        match outcome {
+            InsertOutcome::Inserted => self.item_added(db, inserted_id),
+            InsertOutcome::Merged(id) => self.item_updated(db, id),
+        }
+    }
+
+    pub fn item_added(&self, db: &Database, id: &str) {
+        if let Ok(Some(item)) = db.get_item(id) {
+            self.send(HistoryEvent::ItemAdded(item));
+        }
+    }
+
+    pub fn item_updated(&self, db: &Database, id: &str) {
+        if let Ok(Some(item)) = db.get_item(id) {
+            self.send(HistoryEvent::ItemUpdated(item));
+        }
+    }
+
+    pub fn items_deleted(&self, ids: Vec<String>) {
+        if !ids.is_empty() {
+            self.send(HistoryEvent::ItemDeleted(DeletedItems { ids }));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// ポップアップを開く前にフォーカスされていたウィンドウを記録し、貼り付け時に戻す
+// X11 では _NET_ACTIVE_WINDOW を使う。それ以外の環境ではポップアップを閉じると
+// OS が直前のウィンドウにフォーカスを戻すため、記録・復元は行わない
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct WindowHandle(u32);
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::WindowHandle;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, Window,
+    };
+    use x11rb::rust_connection::RustConnection;
+
+    // _NET_ACTIVE_WINDOW の送信元（2 = ページャなどのツール。ウィンドウマネージャが要求を拒否しにくい）
+    const SOURCE_PAGER: u32 = 2;
+
+    fn connect() -> Result<(RustConnection, Window, Atom), String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        let root = conn.setup().roots[screen_num].root;
+        let active_window = conn
+            .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .atom;
+        Ok((conn, root, active_window))
+    }
+
+    pub fn active_window() -> Result<Option<WindowHandle>, String> {
+        let (conn, root, active_window) = connect()?;
+        let reply = conn
+            .get_property(false, root, active_window, AtomEnum::WINDOW, 0, 1)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+
+        Ok(reply
+            .value32()
+            .and_then(|mut values| values.next())
+            .filter(|&window| window != 0)
+            .map(WindowHandle))
+    }
+
+    pub fn activate(window: WindowHandle) -> Result<(), String> {
+        let (conn, root, active_window) = connect()?;
+        let event = ClientMessageEvent::new(
+            32,
+            window.0,
+            active_window,
+            [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0],
+        );
+        conn.send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )
+        .map_err(|e| e.to_string())?;
+        conn.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// 現在フォーカスされているウィンドウ。取得できない環境では None
+pub fn active_window() -> Option<WindowHandle> {
+    #[cfg(target_os = "linux")]
+    {
+        x11::active_window().unwrap_or_else(|e| {
+            eprintln!("Failed to get active window: {e}");
+            None
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// 記録したウィンドウにフォーカスを戻す
+pub fn activate(window: WindowHandle) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        x11::activate(window)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = window;
+        Ok(())
+    }
+}
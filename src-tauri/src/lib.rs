@@ -1,35 +1,63 @@
 mod clipboard;
 mod content_analyzer;
 mod db;
+mod error;
+mod events;
+mod focus;
 mod models;
+mod paste;
+mod retention;
+mod settings;
+mod shortcuts;
+mod snippets;
+mod transform;
 mod tray;
 mod windows;
 
+use base64::prelude::*;
 use clipboard::ClipboardMonitor;
-use db::Database;
-use models::ClipboardItem;
+use db::{
+    Database, EncryptionStatus, ExportFilter, ExportFormat, ImportMode, ImportOptions,
+    ImportReport, ItemCursor, ItemPage, ItemQuery, RetentionPolicy, RetentionReport,
+};
+use error::ClipediaError;
+use events::{HistoryEvent, HistoryEvents};
+use models::{ClipboardItem, FormatData, SearchMatch, Snippet};
+use retention::RetentionManager;
+use settings::{RestoreTarget, Settings, SettingsStore, ShortcutAction, ShortcutSettings};
+use shortcuts::ShortcutStatus;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tauri::{Manager, State, WindowEvent};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WindowEvent};
+use transform::{Transform, TransformResult};
 
 pub struct AppState {
     pub db: Arc<Mutex<Database>>,
     pub monitor: Arc<ClipboardMonitor>,
+    pub retention: Arc<RetentionManager>,
+    pub settings: Arc<SettingsStore>,
+    pub events: HistoryEvents,
+    // ポップアップを開く前にフォーカスされていたウィンドウ
+    pub previous_window: Arc<Mutex<Option<focus::WindowHandle>>>,
 }
 
 #[tauri::command]
-async fn get_clipboard_history(state: State<'_, AppState>) -> Result<Vec<ClipboardItem>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_items().map_err(|e| e.to_string())
+async fn get_clipboard_history(
+    state: State<'_, AppState>,
+) -> Result<Vec<ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    let items = db.get_all_items()?;
+    Ok(items.into_iter().map(ClipboardItem::masked).collect())
 }
 
 #[tauri::command]
 async fn get_recent_items(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<Vec<ClipboardItem>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let items = db.get_all_items().map_err(|e| e.to_string())?;
+) -> Result<Vec<ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    let items = db.get_all_items()?;
 
     // 最近の5件を取得
     let recent_items: Vec<ClipboardItem> = items.into_iter().take(5).collect();
@@ -37,62 +65,210 @@ async fn get_recent_items(
     // トレイメニューを更新
     let items_for_tray: Vec<(String, String)> = recent_items
         .iter()
-        .map(|item| (item.id.clone(), item.content.clone()))
+        .map(|item| (item.id.clone(), item.display_label()))
         .collect();
 
     let _ = tray::update_recent_items_menu(&app_handle, items_for_tray);
 
-    Ok(recent_items)
+    Ok(recent_items
+        .into_iter()
+        .map(ClipboardItem::masked)
+        .collect())
 }
 
 #[tauri::command]
-async fn copy_to_clipboard(content: String, state: State<'_, AppState>) -> Result<(), String> {
-    state
-        .monitor
-        .copy_to_clipboard(&content)
-        .map_err(|e| e.to_string())
+async fn copy_to_clipboard(
+    content: String,
+    item_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    // アイテムIDが指定された場合は画像などテキスト以外の内容も復元する
+    match item_id {
+        Some(id) => state.monitor.copy_item_to_clipboard(&id, false),
+        None => state.monitor.copy_to_clipboard(&content),
+    }
 }
 
+// 書式付き（HTML など）または書式なしでアイテムをコピーする
+// target を指定しない場合は設定された書き込み先（CLIPBOARD / PRIMARY / 両方）にコピーする
 #[tauri::command]
-async fn pin_item(id: String, is_pinned: bool, state: State<'_, AppState>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_pin_status(&id, is_pinned)
-        .map_err(|e| e.to_string())
+async fn copy_item_as(
+    item_id: String,
+    plain_text: bool,
+    target: Option<RestoreTarget>,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    match target {
+        Some(target) => state.monitor.copy_item_to(&item_id, plain_text, target),
+        None => state.monitor.copy_item_to_clipboard(&item_id, plain_text),
+    }
 }
 
+// ポップアップで選択したアイテムをコピーし、直前のアプリケーションに貼り付ける
 #[tauri::command]
-async fn delete_item(id: String, state: State<'_, AppState>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_item(&id).map_err(|e| e.to_string())
+async fn paste_item(
+    app_handle: tauri::AppHandle,
+    item_id: String,
+    plain_text: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let settings = state.settings.get();
+    state.monitor.copy_item_to(
+        &item_id,
+        plain_text.unwrap_or(false),
+        settings.restore_target.for_paste(),
+    )?;
+
+    if let Some(window) = app_handle.get_webview_window("popup") {
+        let _ = window.close();
+    }
+
+    if !settings.paste_on_select {
+        return Ok(());
+    }
+
+    let previous_window = state.previous_window.lock()?.take();
+    if let Some(window) = previous_window {
+        focus::activate(window).map_err(ClipediaError::Internal)?;
+    }
+
+    // フォーカスが移るのを待ってからキー操作を送る
+    tokio::time::sleep(paste::PASTE_DELAY).await;
+    paste::paste_item(&state.db, &item_id, settings.paste_method)
 }
 
+// テキストアイテムに変換を順番に適用して結果を返す。
+// copy が true なら結果をクリップボードにコピーし、save が true なら新しい履歴アイテムとして保存する
 #[tauri::command]
-async fn delete_all_items(state: State<'_, AppState>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.clear_all().map_err(|e| e.to_string())
+async fn transform_item(
+    item_id: String,
+    transforms: Vec<Transform>,
+    copy: Option<bool>,
+    save: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<TransformResult, ClipediaError> {
+    let item = {
+        let db = state.db.lock()?;
+        db.get_item(&item_id)?
+            .ok_or_else(|| ClipediaError::not_found("item", &item_id))?
+    };
+    if !matches!(item.item_type, models::ClipboardItemType::Text) {
+        return Err(ClipediaError::InvalidInput(
+            "only text items can be transformed".to_string(),
+        ));
+    }
+
+    let content =
+        transform::apply_all(&item.content, &transforms).map_err(ClipediaError::InvalidInput)?;
+
+    // 先にコピーしておくと、監視処理が同じ内容を重複して保存しない
+    if copy.unwrap_or(false) {
+        state.monitor.copy_to_clipboard(&content)?;
+    }
+
+    let mut saved_item_id = None;
+    if save.unwrap_or(false) && !content.trim().is_empty() {
+        let new_item = transform::transformed_item(&item, content.clone());
+        let db = state.db.lock()?;
+        let outcome = db.insert_item(&new_item)?;
+        state.events.inserted(&db, &outcome, &new_item.id);
+        saved_item_id = Some(outcome.id(&new_item.id).to_string());
+    }
+
+    Ok(TransformResult {
+        content,
+        saved_item_id,
+    })
 }
 
 #[tauri::command]
-async fn add_tag(item_id: String, tag: String, state: State<'_, AppState>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.add_tag(&item_id, &tag).map_err(|e| e.to_string())
+async fn get_item_formats(
+    item_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<FormatData>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_formats(&item_id)
+}
+
+#[tauri::command]
+async fn get_image_thumbnail(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, ClipediaError> {
+    let db = state.db.lock()?;
+    let thumbnail = db.get_image_thumbnail(&id)?;
+    Ok(thumbnail.map(|png| png_data_url(&png)))
+}
+
+#[tauri::command]
+async fn get_image_data(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, ClipediaError> {
+    let db = state.db.lock()?;
+    let data = db.get_image_data(&id)?;
+    Ok(data.map(|png| png_data_url(&png)))
+}
+
+fn png_data_url(png: &[u8]) -> String {
+    format!("data:image/png;base64,{}", BASE64_STANDARD.encode(png))
+}
+
+#[tauri::command]
+async fn pin_item(
+    id: String,
+    is_pinned: bool,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.update_pin_status(&id, is_pinned)?;
+    state.events.item_updated(&db, &id);
+    Ok(())
+}
+
+#[tauri::command]
+async fn delete_item(id: String, state: State<'_, AppState>) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.delete_item(&id)?;
+    state.events.items_deleted(vec![id]);
+    Ok(())
+}
+
+#[tauri::command]
+async fn delete_all_items(state: State<'_, AppState>) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.clear_all()?;
+    state.events.send(HistoryEvent::HistoryCleared);
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_tag(
+    item_id: String,
+    tag: String,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.add_tag(&item_id, &tag)?;
+    state.events.item_updated(&db, &item_id);
+    Ok(())
 }
 
 #[tauri::command]
 async fn get_items_paginated(
     offset: i64,
     limit: i64,
+    order: Option<models::ItemOrder>,
     state: State<'_, AppState>,
-) -> Result<Vec<models::ClipboardItem>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_items_paginated(offset, limit)
-        .map_err(|e| e.to_string())
+) -> Result<Vec<models::ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_items_paginated(offset, limit, order.unwrap_or_default())
 }
 
 #[tauri::command]
-async fn get_total_count(state: State<'_, AppState>) -> Result<i64, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_total_count().map_err(|e| e.to_string())
+async fn get_total_count(state: State<'_, AppState>) -> Result<i64, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_total_count()
 }
 
 #[tauri::command]
@@ -100,17 +276,19 @@ async fn remove_tag(
     item_id: String,
     tag: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.remove_tag(&item_id, &tag).map_err(|e| e.to_string())
+) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.remove_tag(&item_id, &tag)?;
+    state.events.item_updated(&db, &item_id);
+    Ok(())
 }
 
 #[tauri::command]
 async fn get_all_tags(
     state: State<'_, AppState>,
-) -> Result<Vec<(String, Option<String>, bool)>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_tags().map_err(|e| e.to_string())
+) -> Result<Vec<(String, Option<String>, bool)>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_all_tags()
 }
 
 #[tauri::command]
@@ -118,10 +296,9 @@ async fn create_custom_tag(
     state: State<'_, AppState>,
     name: String,
     color: Option<String>,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
     db.create_custom_tag(&name, color.as_deref())
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -129,16 +306,15 @@ async fn update_tag_color(
     state: State<'_, AppState>,
     name: String,
     color: String,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
     db.update_tag_color(&name, &color)
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn delete_custom_tag(state: State<'_, AppState>, name: String) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_custom_tag(&name).map_err(|e| e.to_string())
+async fn delete_custom_tag(state: State<'_, AppState>, name: String) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.delete_custom_tag(&name)
 }
 
 #[tauri::command]
@@ -146,43 +322,526 @@ async fn search_items(
     state: State<'_, AppState>,
     pattern: String,
     use_regex: bool,
-) -> Result<Vec<ClipboardItem>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.search_items(&pattern, use_regex)
-        .map_err(|e| e.to_string())
+    include_sensitive: Option<bool>,
+    source: Option<String>,
+) -> Result<Vec<ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.search_items(
+        &pattern,
+        use_regex,
+        include_sensitive.unwrap_or(false),
+        source.as_deref(),
+    )
+}
+
+#[tauri::command]
+async fn search_items_ranked(
+    state: State<'_, AppState>,
+    query: String,
+    use_regex: bool,
+    include_sensitive: Option<bool>,
+    limit: Option<i64>,
+) -> Result<Vec<SearchMatch>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.search_ranked(
+        &query,
+        use_regex,
+        include_sensitive.unwrap_or(false),
+        limit.unwrap_or(100),
+    )
+}
+
+// 検索・タグ・日付などの条件を組み合わせて 1 ページ分取得する
+#[tauri::command]
+async fn query_items(
+    state: State<'_, AppState>,
+    query: ItemQuery,
+) -> Result<ItemPage, ClipediaError> {
+    let db = state.db.lock()?;
+    db.query_items(&query)
+}
+
+// 一覧の読み込み後に追加されたアイテム（新しい順）
+#[tauri::command]
+async fn get_items_since(
+    state: State<'_, AppState>,
+    cursor: ItemCursor,
+    limit: Option<i64>,
+) -> Result<Vec<ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_items_since(&cursor, limit.unwrap_or(100))
 }
 
 #[tauri::command]
 async fn get_items_by_tag(
     state: State<'_, AppState>,
     tag: String,
-) -> Result<Vec<ClipboardItem>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_items_by_tag(&tag).map_err(|e| e.to_string())
+) -> Result<Vec<ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_items_by_tag(&tag)
+}
+
+#[tauri::command]
+async fn get_items_by_source(
+    state: State<'_, AppState>,
+    source: String,
+) -> Result<Vec<ClipboardItem>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_items_by_source(&source)
+}
+
+// コピー元アプリケーションの一覧（アイテム数付き）
+#[tauri::command]
+async fn get_application_sources(
+    state: State<'_, AppState>,
+) -> Result<Vec<(String, i64)>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_application_sources()
 }
 
 #[tauri::command]
 async fn update_tray_menu(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let items = db.get_all_items().map_err(|e| e.to_string())?;
+) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    let items = db.get_all_items()?;
 
     // 最近の5件を取得してトレイメニューを更新
     let items_for_tray: Vec<(String, String)> = items
         .into_iter()
         .take(5)
-        .map(|item| (item.id, item.content))
+        .map(|item| (item.id.clone(), item.display_label()))
         .collect();
 
-    tray::update_recent_items_menu(&app_handle, items_for_tray).map_err(|e| e.to_string())
+    tray::update_recent_items_menu(&app_handle, items_for_tray)
+        .map_err(|e| ClipediaError::Internal(e.to_string()))
+}
+
+#[tauri::command]
+async fn get_settings(state: State<'_, AppState>) -> Result<Settings, ClipediaError> {
+    Ok(state.settings.get())
+}
+
+#[tauri::command]
+async fn update_settings(
+    app_handle: tauri::AppHandle,
+    settings: Settings,
+    state: State<'_, AppState>,
+) -> Result<Settings, ClipediaError> {
+    settings.validate().map_err(ClipediaError::InvalidInput)?;
+    let current = state.settings.get();
+    let new_shortcuts = settings.shortcuts.clone();
+    let shortcuts_changed = current.shortcuts != new_shortcuts;
+    if shortcuts_changed {
+        // 他のアプリケーションが使用中のショートカット
+        shortcuts::apply(&app_handle, &current.shortcuts, &new_shortcuts)
+            .map_err(ClipediaError::Conflict)?;
+    }
+
+    let settings = match state.settings.update(settings) {
+        Ok(settings) => settings,
+        Err(e) => {
+            // 保存できなかった場合は登録したショートカットを保存済みの設定に戻す
+            if shortcuts_changed {
+                let _ = shortcuts::apply(&app_handle, &new_shortcuts, &current.shortcuts);
+            }
+            return Err(ClipediaError::Internal(e));
+        }
+    };
+    apply_settings(&app_handle, &settings);
+    Ok(settings)
+}
+
+#[tauri::command]
+async fn get_shortcut_status(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<ShortcutStatus>, ClipediaError> {
+    Ok(shortcuts::status(
+        &app_handle,
+        &state.settings.get().shortcuts,
+    ))
+}
+
+// ショートカットを割り当てる。他のアプリケーションと競合する場合はエラーを返す
+#[tauri::command]
+async fn register_shortcut(
+    app_handle: tauri::AppHandle,
+    action: ShortcutAction,
+    accelerator: String,
+    state: State<'_, AppState>,
+) -> Result<ShortcutSettings, ClipediaError> {
+    let mut settings = state.settings.get();
+    settings.shortcuts.set(action, Some(accelerator));
+    update_settings(app_handle, settings, state)
+        .await
+        .map(|settings| settings.shortcuts)
+}
+
+#[tauri::command]
+async fn unregister_shortcut(
+    app_handle: tauri::AppHandle,
+    action: ShortcutAction,
+    state: State<'_, AppState>,
+) -> Result<ShortcutSettings, ClipediaError> {
+    let mut settings = state.settings.get();
+    settings.shortcuts.set(action, None);
+    update_settings(app_handle, settings, state)
+        .await
+        .map(|settings| settings.shortcuts)
+}
+
+// 以下の 4 つは設定の一部を読み書きする（以前のコマンドとの互換用）
+#[tauri::command]
+async fn get_sensitive_ttl(state: State<'_, AppState>) -> Result<u64, ClipediaError> {
+    Ok(state.settings.get().sensitive_ttl_minutes)
+}
+
+#[tauri::command]
+async fn set_sensitive_ttl(
+    app_handle: tauri::AppHandle,
+    minutes: u64,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let mut settings = state.settings.get();
+    settings.sensitive_ttl_minutes = minutes;
+    update_settings(app_handle, settings, state).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_retention_policy(
+    state: State<'_, AppState>,
+) -> Result<RetentionPolicy, ClipediaError> {
+    Ok(state.settings.get().retention_policy())
+}
+
+#[tauri::command]
+async fn set_retention_policy(
+    app_handle: tauri::AppHandle,
+    policy: RetentionPolicy,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let mut settings = state.settings.get();
+    // 設定では最大件数を無制限にできないため、指定がない場合は現在の値のままにする
+    if let Some(max_items) = policy.max_items {
+        settings.max_history_size = max_items;
+    }
+    settings.max_history_age_days = policy.max_age_days;
+    settings.max_history_bytes = policy.max_total_bytes;
+    settings.vacuum_after_prune = policy.vacuum;
+    update_settings(app_handle, settings, state).await?;
+    Ok(())
+}
+
+// 変更された設定を監視・保存上限・トレイに反映し、各ウィンドウに通知する
+fn apply_settings<R: Runtime>(app: &AppHandle<R>, settings: &Settings) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.monitor.set_monitoring(settings.monitoring_enabled);
+        // validate 済みのため失敗しない
+        if let Ok(exclusions) = settings.exclusion_rules() {
+            state.monitor.set_exclusions(exclusions);
+        }
+        state
+            .monitor
+            .set_primary_selection(settings.primary_selection());
+        state.retention.set_policy(settings.retention_policy());
+    }
+    let _ = tray::refresh_menu(app);
+    let _ = app.emit("settings-changed", settings);
+}
+
+// 監視の有効/無効を切り替えて設定に保存する
+pub(crate) fn set_monitoring_enabled<R: Runtime>(
+    app: &AppHandle<R>,
+    enabled: bool,
+) -> Result<bool, ClipediaError> {
+    let state = app
+        .try_state::<AppState>()
+        .ok_or_else(|| ClipediaError::Internal("app state is not initialized".to_string()))?;
+    let settings = state
+        .settings
+        .modify(|s| s.monitoring_enabled = enabled)
+        .map_err(ClipediaError::Internal)?;
+    apply_settings(app, &settings);
+    Ok(settings.monitoring_enabled)
+}
+
+// 履歴とタグをファイルに書き出す（JSON はすべての種類、CSV はテキストアイテムのみ）
+#[tauri::command]
+async fn export_history(
+    path: String,
+    format: Option<ExportFormat>,
+    filter: Option<ExportFilter>,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let filter = filter.unwrap_or_default();
+    let data = {
+        let db = state.db.lock()?;
+        match format.unwrap_or_default() {
+            ExportFormat::Json => {
+                let export = db.export_history(&filter)?;
+                serde_json::to_string_pretty(&export)
+                    .map_err(|e| ClipediaError::Internal(e.to_string()))?
+            }
+            ExportFormat::Csv => db.export_csv(&filter)?,
+        }
+    };
+    std::fs::write(&path, data)?;
+    Ok(())
+}
+
+// export_history で書き出した JSON を読み込む
+#[tauri::command]
+async fn import_history(
+    path: String,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, ClipediaError> {
+    let json = std::fs::read_to_string(&path)?;
+    let export = db::parse_export(&json)?;
+
+    let options = options.unwrap_or_default();
+    let db = state.db.lock()?;
+    let report = db.import_history(&export, &options)?;
+
+    if options.mode == ImportMode::Replace {
+        state.events.send(HistoryEvent::HistoryCleared);
+    }
+    for id in &report.added_ids {
+        state.events.item_added(&db, id);
+    }
+    for id in &report.updated_ids {
+        state.events.item_updated(&db, id);
+    }
+    Ok(report)
 }
 
 #[tauri::command]
-async fn toggle_monitoring(state: State<'_, AppState>) -> Result<bool, String> {
-    let is_enabled = state.monitor.toggle_monitoring();
-    Ok(is_enabled)
+async fn run_retention(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RetentionReport, ClipediaError> {
+    state.retention.run_once(&app_handle)
+}
+
+#[tauri::command]
+async fn get_encryption_status(
+    state: State<'_, AppState>,
+) -> Result<EncryptionStatus, ClipediaError> {
+    let db = state.db.lock()?;
+    Ok(db.encryption_status())
+}
+
+#[tauri::command]
+async fn enable_encryption(
+    passphrase: String,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let mut db = state.db.lock()?;
+    db.enable_encryption(&passphrase)
+}
+
+#[tauri::command]
+async fn change_passphrase(
+    current: String,
+    new: String,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let mut db = state.db.lock()?;
+    db.change_passphrase(&current, &new)
+}
+
+#[tauri::command]
+async fn lock_database(state: State<'_, AppState>) -> Result<(), ClipediaError> {
+    let mut db = state.db.lock()?;
+    db.lock()
+}
+
+#[tauri::command]
+async fn unlock_database(
+    passphrase: String,
+    state: State<'_, AppState>,
+) -> Result<(), ClipediaError> {
+    let mut db = state.db.lock()?;
+    db.unlock(&passphrase)
+}
+
+#[tauri::command]
+async fn toggle_monitoring(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<bool, ClipediaError> {
+    set_monitoring_enabled(&app_handle, !state.monitor.is_monitoring())
+}
+
+#[tauri::command]
+async fn get_snippets(state: State<'_, AppState>) -> Result<Vec<Snippet>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.get_snippets()
+}
+
+#[tauri::command]
+async fn search_snippets(
+    query: String,
+    limit: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Snippet>, ClipediaError> {
+    let db = state.db.lock()?;
+    db.search_snippets(&query, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+async fn create_snippet(
+    name: String,
+    abbreviation: Option<String>,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<Snippet, ClipediaError> {
+    let snippet = Snippet::new(name, abbreviation, content);
+    snippet.validate().map_err(ClipediaError::InvalidInput)?;
+
+    let db = state.db.lock()?;
+    db.insert_snippet(&snippet)?;
+    Ok(snippet)
+}
+
+#[tauri::command]
+async fn update_snippet(
+    id: String,
+    name: String,
+    abbreviation: Option<String>,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<Snippet, ClipediaError> {
+    let db = state.db.lock()?;
+    let mut snippet = db
+        .get_snippet(&id)?
+        .ok_or_else(|| ClipediaError::not_found("snippet", &id))?;
+    snippet.name = name.trim().to_string();
+    snippet.abbreviation = models::snippet::normalize_abbreviation(abbreviation);
+    snippet.content = content;
+    snippet.updated_at = chrono::Utc::now();
+    snippet.validate().map_err(ClipediaError::InvalidInput)?;
+
+    db.update_snippet(&snippet)?;
+    Ok(snippet)
+}
+
+#[tauri::command]
+async fn delete_snippet(id: String, state: State<'_, AppState>) -> Result<(), ClipediaError> {
+    let db = state.db.lock()?;
+    db.delete_snippet(&id)
+}
+
+// コピーする前に入力してもらう {{input:名前}} の名前
+#[tauri::command]
+async fn get_snippet_inputs(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, ClipediaError> {
+    let db = state.db.lock()?;
+    let snippet = db
+        .get_snippet(&id)?
+        .ok_or_else(|| ClipediaError::not_found("snippet", &id))?;
+    Ok(snippets::input_names(&snippet.content))
+}
+
+// プレースホルダーを展開してクリップボードにコピーし、展開後のテキストを返す
+#[tauri::command]
+async fn copy_snippet(
+    id: String,
+    inputs: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+) -> Result<String, ClipediaError> {
+    let snippet = {
+        let db = state.db.lock()?;
+        db.get_snippet(&id)?
+            .ok_or_else(|| ClipediaError::not_found("snippet", &id))?
+    };
+
+    let clipboard = state.monitor.clipboard_text();
+    let inputs = inputs.unwrap_or_default();
+    let expanded = snippets::expand(
+        &snippet.content,
+        &snippets::ExpandContext {
+            now: chrono::Local::now(),
+            clipboard: clipboard.as_deref(),
+            inputs: &inputs,
+        },
+    )
+    .map_err(ClipediaError::InvalidInput)?;
+
+    state.monitor.copy_to_clipboard(&expanded)?;
+    Ok(expanded)
+}
+
+// 履歴のテキストアイテムをスニペットとして保存する。名前を省略した場合は本文の最初の行を使う
+#[tauri::command]
+async fn promote_item_to_snippet(
+    item_id: String,
+    name: Option<String>,
+    abbreviation: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Snippet, ClipediaError> {
+    let db = state.db.lock()?;
+    let item = db
+        .get_item(&item_id)?
+        .ok_or_else(|| ClipediaError::not_found("item", &item_id))?;
+    if !matches!(item.item_type, models::ClipboardItemType::Text) {
+        return Err(ClipediaError::InvalidInput(
+            "only text items can be saved as snippets".to_string(),
+        ));
+    }
+
+    let name = name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| {
+            let first_line = item
+                .content
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .unwrap_or_default();
+            first_line.chars().take(40).collect()
+        });
+    let snippet = Snippet::new(name, abbreviation, item.content);
+    snippet.validate().map_err(ClipediaError::InvalidInput)?;
+
+    db.insert_snippet(&snippet)?;
+    Ok(snippet)
+}
+
+// 履歴の変更をイベントとしてフロントエンドに送信し、トレイの最近のアイテムを更新する
+async fn forward_history_events(
+    app_handle: AppHandle,
+    mut receiver: tokio::sync::mpsc::UnboundedReceiver<HistoryEvent>,
+) {
+    while let Some(event) = receiver.recv().await {
+        emit_history_event(&app_handle, event);
+        // まとめて届いた変更はトレイの更新を1回で済ませる
+        while let Ok(event) = receiver.try_recv() {
+            emit_history_event(&app_handle, event);
+        }
+        if let Err(e) = tray::refresh_menu(&app_handle) {
+            eprintln!("Failed to refresh tray menu: {e}");
+        }
+    }
+}
+
+fn emit_history_event(app_handle: &AppHandle, event: HistoryEvent) {
+    let name = event.name();
+    let result = match event {
+        HistoryEvent::ItemAdded(item) | HistoryEvent::ItemUpdated(item) => {
+            app_handle.emit(name, item.masked())
+        }
+        HistoryEvent::ItemDeleted(deleted) => app_handle.emit(name, deleted),
+        HistoryEvent::HistoryCleared => app_handle.emit(name, ()),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to emit {name}: {e}");
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -197,10 +856,35 @@ pub fn run() {
             let app_dir = app_handle.path().app_data_dir().unwrap();
             std::fs::create_dir_all(&app_dir).unwrap();
 
+            // ウィンドウやバックグラウンド処理より先に設定を読み込む
+            let settings = Arc::new(SettingsStore::load(&app_dir.join("settings.json")));
+            let initial_settings = settings.get();
+
             let db_path = app_dir.join("clipedia.db");
             let db = Arc::new(Mutex::new(Database::new(&db_path).unwrap()));
 
-            let monitor = Arc::new(ClipboardMonitor::new(db.clone()).unwrap());
+            // 履歴の変更をフロントエンドとトレイに反映する
+            let (events, receiver) = HistoryEvents::channel();
+            let app_handle_clone = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                forward_history_events(app_handle_clone, receiver).await;
+            });
+
+            let retention = Arc::new(RetentionManager::new(
+                db.clone(),
+                initial_settings.retention_policy(),
+                events.clone(),
+            ));
+
+            let monitor = Arc::new(
+                ClipboardMonitor::new(db.clone(), retention.trigger(), events.clone()).unwrap(),
+            );
+            monitor.set_monitoring(initial_settings.monitoring_enabled);
+            match initial_settings.exclusion_rules() {
+                Ok(exclusions) => monitor.set_exclusions(exclusions),
+                Err(e) => eprintln!("Invalid exclusion settings: {e}"),
+            }
+            monitor.set_primary_selection(initial_settings.primary_selection());
             let monitor_clone = monitor.clone();
 
             // Start clipboard monitoring in background
@@ -208,15 +892,49 @@ pub fn run() {
                 monitor_clone.start_monitoring().await;
             });
 
-            app.manage(AppState { db, monitor });
+            // 保存期間を過ぎた機密情報を定期的に削除
+            let db_clone = db.clone();
+            let settings_clone = settings.clone();
+            let events_clone = events.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let minutes = settings_clone.get().sensitive_ttl_minutes;
+                    if minutes > 0 {
+                        if let Ok(db) = db_clone.lock() {
+                            // ロック中は削除できないので次回に持ち越す
+                            if !db.is_locked() {
+                                let ttl = chrono::Duration::minutes(minutes as i64);
+                                match db.delete_expired_sensitive(ttl) {
+                                    Ok(ids) => events_clone.items_deleted(ids),
+                                    Err(e) => {
+                                        eprintln!("Failed to delete expired sensitive items: {e}")
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                }
+            });
 
-            // グローバルホットキーの登録
+            // 履歴の保存上限を適用
+            let retention_clone = retention.clone();
             let app_handle_clone = app_handle.clone();
-            app.global_shortcut()
-                .on_shortcut("Alt+Z", move |_app_handle, _shortcut, _event| {
-                    let _ = show_popup_window(&app_handle_clone);
-                })
-                .unwrap();
+            tauri::async_runtime::spawn(async move {
+                retention_clone.start(app_handle_clone).await;
+            });
+
+            app.manage(AppState {
+                db,
+                monitor,
+                retention,
+                settings,
+                events,
+                previous_window: Arc::new(Mutex::new(None)),
+            });
+
+            // グローバルホットキーの登録
+            shortcuts::register_all(app_handle, &initial_settings.shortcuts);
 
             // システムトレイの作成
             tray::create_tray(app_handle)?;
@@ -225,18 +943,38 @@ pub fn run() {
             let main_window = app.get_webview_window("main").unwrap();
             let app_handle_clone = app_handle.clone();
             main_window.on_window_event(move |event| {
-                if let WindowEvent::CloseRequested { api, .. } = event {
-                    // 閉じるボタンでトレイに最小化（設定により変更可能）
-                    api.prevent_close();
-                    if let Some(window) = app_handle_clone.get_webview_window("main") {
-                        let _ = tray::minimize_to_tray(&window);
+                let Some(state) = app_handle_clone.try_state::<AppState>() else {
+                    return;
+                };
+                let settings = state.settings.get();
+                let Some(window) = app_handle_clone.get_webview_window("main") else {
+                    return;
+                };
+
+                match event {
+                    WindowEvent::CloseRequested { api, .. } => {
+                        // 閉じるボタンでトレイに最小化。無効の場合はアプリを終了する
+                        if settings.close_to_tray {
+                            api.prevent_close();
+                            let _ = tray::minimize_to_tray(&window);
+                        } else {
+                            app_handle_clone.exit(0);
+                        }
+                    }
+                    WindowEvent::Resized(_) => {
+                        // 最小化時にタスクバーではなくトレイに格納する
+                        if settings.minimize_to_tray && window.is_minimized().unwrap_or(false) {
+                            let _ = tray::minimize_to_tray(&window);
+                        }
                     }
+                    _ => {}
                 }
             });
 
-            // 初回起動時はメインウィンドウを表示
-            // TODO: 設定から起動時の動作を制御できるようにする
-            let _ = main_window.show();
+            // 最小化して起動する設定の場合はトレイに格納したままにする
+            if !initial_settings.start_minimized {
+                let _ = main_window.show();
+            }
 
             Ok(())
         })
@@ -244,6 +982,12 @@ pub fn run() {
             get_clipboard_history,
             get_recent_items,
             copy_to_clipboard,
+            copy_item_as,
+            paste_item,
+            transform_item,
+            get_item_formats,
+            get_image_thumbnail,
+            get_image_data,
             pin_item,
             delete_item,
             delete_all_items,
@@ -257,8 +1001,38 @@ pub fn run() {
             update_tag_color,
             delete_custom_tag,
             search_items,
+            search_items_ranked,
+            query_items,
+            get_items_since,
             get_items_by_tag,
-            toggle_monitoring
+            get_items_by_source,
+            get_application_sources,
+            get_settings,
+            update_settings,
+            get_shortcut_status,
+            register_shortcut,
+            unregister_shortcut,
+            get_sensitive_ttl,
+            set_sensitive_ttl,
+            get_retention_policy,
+            set_retention_policy,
+            run_retention,
+            export_history,
+            import_history,
+            get_encryption_status,
+            enable_encryption,
+            change_passphrase,
+            lock_database,
+            unlock_database,
+            toggle_monitoring,
+            get_snippets,
+            search_snippets,
+            create_snippet,
+            update_snippet,
+            delete_snippet,
+            get_snippet_inputs,
+            copy_snippet,
+            promote_item_to_snippet
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
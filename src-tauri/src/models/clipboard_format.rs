@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+// プレーンテキスト以外の表現形式
+// プレーンテキストは clipboard_items.content に保存されている
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardFormat {
+    Html,
+    Rtf,
+}
+
+impl ClipboardFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ClipboardFormat::Html => "text/html",
+            ClipboardFormat::Rtf => "text/rtf",
+        }
+    }
+
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type {
+            "text/html" => Some(ClipboardFormat::Html),
+            "text/rtf" => Some(ClipboardFormat::Rtf),
+            _ => None,
+        }
+    }
+}
+
+// アイテムに保存されている表現の一つ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatData {
+    pub format: ClipboardFormat,
+    pub data: String,
+}
@@ -1,3 +1,5 @@
+use super::{FileEntry, ImageInfo};
+use crate::content_analyzer::{ContentAnalyzer, SENSITIVE_TAG};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -13,6 +15,16 @@ pub struct ClipboardItem {
     pub is_pinned: bool,
     pub tags: Vec<String>,
     pub application_source: Option<String>,
+    // 画像アイテムの場合のみ設定される
+    pub image: Option<ImageInfo>,
+    // ファイルアイテムの場合のみ設定される
+    pub files: Option<Vec<FileEntry>>,
+    // 同じ内容がコピーされた回数（履歴からのコピーを含む）
+    pub copy_count: i64,
+    // 最後に再度コピーされた日時。一度しかコピーされていない場合は None
+    pub last_used_at: Option<DateTime<Utc>>,
+    // 取得したセレクション
+    pub selection: Selection,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +35,54 @@ pub enum ClipboardItemType {
     File,
 }
 
+impl ClipboardItemType {
+    // データベースに保存する文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipboardItemType::Text => "text",
+            ClipboardItemType::Image => "image",
+            ClipboardItemType::File => "file",
+        }
+    }
+}
+
+/// アイテムを取得したセレクション。Primary は Linux でマウスで選択しただけのテキスト
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    #[default]
+    Clipboard,
+    Primary,
+}
+
+impl Selection {
+    // データベースに保存する文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Selection::Clipboard => "clipboard",
+            Selection::Primary => "primary",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "primary" => Selection::Primary,
+            _ => Selection::Clipboard,
+        }
+    }
+}
+
+/// 履歴の並び順（ピン留めされたアイテムは常に先頭）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemOrder {
+    #[default]
+    Newest,
+    Oldest,
+    // コピーされた回数の多い順
+    MostUsed,
+}
+
 impl ClipboardItem {
     pub fn new(content: String, item_type: ClipboardItemType) -> Self {
         Self {
@@ -33,6 +93,45 @@ impl ClipboardItem {
             is_pinned: false,
             tags: Vec::new(),
             application_source: None,
+            image: None,
+            files: None,
+            copy_count: 1,
+            last_used_at: None,
+            selection: Selection::Clipboard,
+        }
+    }
+
+    pub fn is_sensitive(&self) -> bool {
+        self.tags.iter().any(|tag| tag == SENSITIVE_TAG)
+    }
+
+    // 機密情報を含む場合は内容を伏せ字にする
+    pub fn masked(mut self) -> Self {
+        if self.is_sensitive() {
+            self.content = ContentAnalyzer::mask(&self.content);
+        }
+        self
+    }
+
+    // トレイなどに表示する短いラベル
+    pub fn display_label(&self) -> String {
+        if self.is_sensitive() {
+            return ContentAnalyzer::mask(&self.content);
+        }
+
+        match (&self.item_type, &self.files) {
+            (ClipboardItemType::File, Some(files)) if !files.is_empty() => {
+                let first = std::path::Path::new(&files[0].path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| files[0].path.clone());
+                if files.len() > 1 {
+                    format!("ファイル: {first} 他{}件", files.len() - 1)
+                } else {
+                    format!("ファイル: {first}")
+                }
+            }
+            _ => self.content.clone(),
         }
     }
 }
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// ファイルアイテムに含まれる各パスの情報（コピー時点のもの）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    pub path: String,
+    pub exists: bool,
+    pub is_dir: bool,
+    pub size: Option<i64>,
+    pub mime_type: Option<String>,
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+// 画像アイテムのメタデータ（画像本体は含まない）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    // PNG エンコード後のサイズ
+    pub byte_size: i64,
+}
+
+// データベースに保存する画像
+#[derive(Debug, Clone)]
+pub struct EncodedImage {
+    pub info: ImageInfo,
+    // 元の RGBA データの SHA-256（重複検出用）
+    pub hash: String,
+    pub png: Vec<u8>,
+    pub thumbnail_png: Vec<u8>,
+}
@@ -1,3 +1,13 @@
+pub mod clipboard_format;
 pub mod clipboard_item;
+pub mod file_entry;
+pub mod image;
+pub mod search_match;
+pub mod snippet;
 
-pub use clipboard_item::{ClipboardItem, ClipboardItemType};
+pub use clipboard_format::{ClipboardFormat, FormatData};
+pub use clipboard_item::{ClipboardItem, ClipboardItemType, ItemOrder, Selection};
+pub use file_entry::FileEntry;
+pub use image::{EncodedImage, ImageInfo};
+pub use search_match::SearchMatch;
+pub use snippet::Snippet;
@@ -0,0 +1,15 @@
+use super::ClipboardItem;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub item: ClipboardItem,
+    // 値が小さいほど関連度が高い（FTS5 の bm25）
+    pub rank: f64,
+    pub snippet: String,
+    // snippet 内の一致箇所（文字単位の [開始, 終了)）
+    pub snippet_highlights: Vec<(usize, usize)>,
+    // content 全体での一致箇所（文字単位の [開始, 終了)）
+    pub highlights: Vec<(usize, usize)>,
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 名前を付けて保存した定型文。本文には {{date}} などのプレースホルダーを含められる
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    // 検索欄に入力して呼び出すための短い名前（任意、重複不可）
+    pub abbreviation: Option<String>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Snippet {
+    pub fn new(name: String, abbreviation: Option<String>, content: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: name.trim().to_string(),
+            abbreviation: normalize_abbreviation(abbreviation),
+            content,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("snippet name must not be empty".to_string());
+        }
+        if self
+            .abbreviation
+            .as_deref()
+            .is_some_and(|abbreviation| abbreviation.contains(char::is_whitespace))
+        {
+            return Err("snippet abbreviation must not contain whitespace".to_string());
+        }
+        Ok(())
+    }
+}
+
+// 空の略語は設定なしとして扱う
+pub fn normalize_abbreviation(abbreviation: Option<String>) -> Option<String> {
+    abbreviation
+        .map(|abbreviation| abbreviation.trim().to_string())
+        .filter(|abbreviation| !abbreviation.is_empty())
+}
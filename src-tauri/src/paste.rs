@@ -0,0 +1,84 @@
+use crate::db::Database;
+use crate::error::{ClipediaError, Result};
+use crate::models::ClipboardItemType;
+use crate::settings::PasteMethod;
+use enigo::{Direction, Enigo, Key, Keyboard};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// フォーカスの切り替えやショートカットの修飾キーが離されるのを待つ時間
+pub const PASTE_DELAY: Duration = Duration::from_millis(150);
+
+// macOS では Ctrl の代わりに Command を使う
+#[cfg(target_os = "macos")]
+const PRIMARY_MODIFIER: Key = Key::Meta;
+#[cfg(not(target_os = "macos"))]
+const PRIMARY_MODIFIER: Key = Key::Control;
+
+fn chord(enigo: &mut Enigo, modifiers: &[Key], key: Key) -> enigo::InputResult<()> {
+    let mut pressed = 0;
+    let mut result = Ok(());
+    for modifier in modifiers {
+        result = enigo.key(*modifier, Direction::Press);
+        if result.is_err() {
+            break;
+        }
+        pressed += 1;
+    }
+    if result.is_ok() {
+        result = enigo.key(key, Direction::Click);
+    }
+
+    // 途中で失敗しても修飾キーが押されたままにならないよう、押したキーはすべて離す。
+    // 離すのに失敗した場合も残りのキーを離してから最初のエラーを返す
+    let mut release_result = Ok(());
+    for modifier in modifiers[..pressed].iter().rev() {
+        let released = enigo.key(*modifier, Direction::Release);
+        if release_result.is_ok() {
+            release_result = released;
+        }
+    }
+    result.and(release_result)
+}
+
+/// フォーカスされているアプリケーションに貼り付けのキー操作を送る。
+/// Type の場合はクリップボードを使わず text を直接入力する
+fn simulate(method: PasteMethod, text: &str) -> Result<()> {
+    let mut enigo = Enigo::new(&enigo::Settings::default())
+        .map_err(|e| ClipediaError::Internal(e.to_string()))?;
+
+    match method {
+        PasteMethod::CtrlV => chord(&mut enigo, &[PRIMARY_MODIFIER], Key::Unicode('v')),
+        #[cfg(not(target_os = "macos"))]
+        PasteMethod::ShiftInsert => chord(&mut enigo, &[Key::Shift], Key::Insert),
+        // macOS には Insert キーがないため通常の貼り付けを使う
+        #[cfg(target_os = "macos")]
+        PasteMethod::ShiftInsert => chord(&mut enigo, &[PRIMARY_MODIFIER], Key::Unicode('v')),
+        PasteMethod::CtrlShiftV => chord(
+            &mut enigo,
+            &[PRIMARY_MODIFIER, Key::Shift],
+            Key::Unicode('v'),
+        ),
+        PasteMethod::Type => enigo.text(text),
+    }
+    .map_err(|e| ClipediaError::Internal(e.to_string()))
+}
+
+/// クリップボードにコピー済みのアイテムを貼り付ける。
+/// テキスト以外のアイテムは入力できないため、Type の場合も Ctrl+V で貼り付ける
+pub fn paste_item(db: &Mutex<Database>, item_id: &str, method: PasteMethod) -> Result<()> {
+    if method != PasteMethod::Type {
+        return simulate(method, "");
+    }
+
+    let item = {
+        let db = db.lock()?;
+        db.get_item(item_id)?
+    };
+    match item {
+        Some(item) if matches!(item.item_type, ClipboardItemType::Text) => {
+            simulate(PasteMethod::Type, &item.content)
+        }
+        _ => simulate(PasteMethod::CtrlV, ""),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::db::{Database, RetentionPolicy, RetentionReport};
+use crate::error::Result;
+use crate::events::HistoryEvents;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::sync::Notify;
+
+// 定期的に保存上限をチェックする間隔
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 履歴の保存上限を適用する。
+/// アイテムの追加時（trigger の Notify で通知）と一定間隔ごとに実行し、削除があれば `retention-pruned` イベントを送信する
+pub struct RetentionManager {
+    db: Arc<Mutex<Database>>,
+    policy: Mutex<RetentionPolicy>,
+    trigger: Arc<Notify>,
+    events: HistoryEvents,
+}
+
+impl RetentionManager {
+    pub fn new(db: Arc<Mutex<Database>>, policy: RetentionPolicy, events: HistoryEvents) -> Self {
+        Self {
+            db,
+            policy: Mutex::new(policy),
+            trigger: Arc::new(Notify::new()),
+            events,
+        }
+    }
+
+    /// アイテム追加時に通知するためのハンドル
+    pub fn trigger(&self) -> Arc<Notify> {
+        self.trigger.clone()
+    }
+
+    pub fn policy(&self) -> RetentionPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    pub fn set_policy(&self, policy: RetentionPolicy) {
+        *self.policy.lock().unwrap() = policy;
+        self.trigger.notify_one();
+    }
+
+    pub fn run_once<R: Runtime>(&self, app: &AppHandle<R>) -> Result<RetentionReport> {
+        let policy = self.policy();
+        let report = {
+            let db = self.db.lock()?;
+            // ロック中は何もしない
+            if db.is_locked() {
+                return Ok(RetentionReport::default());
+            }
+            db.apply_retention(&policy)?
+        };
+
+        if !report.is_empty() {
+            let _ = app.emit("retention-pruned", &report);
+            self.events.items_deleted(report.removed_ids.clone());
+        }
+
+        Ok(report)
+    }
+
+    pub async fn start<R: Runtime>(&self, app: AppHandle<R>) {
+        loop {
+            if let Err(e) = self.run_once(&app) {
+                eprintln!("Failed to apply retention policy: {e}");
+            }
+
+            tokio::select! {
+                _ = self.trigger.notified() => {}
+                _ = tokio::time::sleep(RETENTION_INTERVAL) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::clipboard::exclusion::ExclusionRules;
+use crate::clipboard::primary::PrimarySelection;
+use crate::db::RetentionPolicy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// アプリケーションの設定。`settings.json` として app_data_dir に保存する
+/// 古いバージョンで保存されたファイルでも読み込めるよう、不足している項目はデフォルト値で補う
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    // 起動時の動作
+    pub start_minimized: bool,
+    // 自動起動の登録は OS ごとの対応が必要なため、現状は保存のみ
+    pub start_with_system: bool,
+    pub remember_last_state: bool,
+
+    // トレイの動作
+    pub minimize_to_tray: bool,
+    pub close_to_tray: bool,
+
+    // 通知設定
+    pub show_notifications: bool,
+
+    // クリップボード監視
+    pub monitoring_enabled: bool,
+    pub max_history_size: u32,
+    pub max_history_age_days: Option<u32>,
+    pub max_history_bytes: Option<i64>,
+    pub vacuum_after_prune: bool,
+    // 記録しないアプリケーション（WM_CLASS などのアプリケーション名）とウィンドウタイトルの正規表現
+    pub excluded_apps: Vec<String>,
+    pub excluded_window_titles: Vec<String>,
+
+    // 機密情報を自動削除するまでの時間（分）。0 の場合は削除しない
+    pub sensitive_ttl_minutes: u64,
+
+    // Linux の PRIMARY セレクション（マウスで選択したテキスト）も記録する。
+    // ドラッグ中の選択範囲を記録しないよう、この時間（ミリ秒）変化しなかった選択だけを記録する
+    pub capture_primary_selection: bool,
+    pub primary_selection_debounce_ms: u64,
+    // 履歴のアイテムをコピーするときの書き込み先
+    pub restore_target: RestoreTarget,
+
+    // グローバルショートカット
+    pub shortcuts: ShortcutSettings,
+
+    // ポップアップでアイテムを選択したときに直前のアプリケーションへ貼り付ける
+    pub paste_on_select: bool,
+    pub paste_method: PasteMethod,
+
+    // 自動更新
+    pub auto_update: bool,
+    pub check_update_on_startup: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            start_minimized: false,
+            start_with_system: false,
+            remember_last_state: true,
+            minimize_to_tray: true,
+            close_to_tray: true,
+            show_notifications: true,
+            monitoring_enabled: true,
+            max_history_size: 1000,
+            max_history_age_days: None,
+            max_history_bytes: None,
+            vacuum_after_prune: false,
+            excluded_apps: vec![
+                "KeePassXC".to_string(),
+                "1Password".to_string(),
+                "Bitwarden".to_string(),
+            ],
+            excluded_window_titles: Vec::new(),
+            sensitive_ttl_minutes: 60,
+            capture_primary_selection: false,
+            primary_selection_debounce_ms: 500,
+            restore_target: RestoreTarget::default(),
+            shortcuts: ShortcutSettings::default(),
+            paste_on_select: true,
+            paste_method: PasteMethod::default(),
+            auto_update: true,
+            check_update_on_startup: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(10..=1_000_000).contains(&self.max_history_size) {
+            return Err("maxHistorySize must be between 10 and 1000000".to_string());
+        }
+        if self.max_history_age_days == Some(0) {
+            return Err("maxHistoryAgeDays must be at least 1".to_string());
+        }
+        if self.max_history_bytes.is_some_and(|bytes| bytes <= 0) {
+            return Err("maxHistoryBytes must be positive".to_string());
+        }
+        if self.primary_selection_debounce_ms > 10_000 {
+            return Err("primarySelectionDebounceMs must be at most 10000".to_string());
+        }
+        self.exclusion_rules()?;
+        for (action, accelerator) in self.shortcuts.iter() {
+            if accelerator.trim().is_empty() {
+                return Err(format!("shortcut for {action:?} must not be empty"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn exclusion_rules(&self) -> Result<ExclusionRules, String> {
+        ExclusionRules::new(&self.excluded_apps, &self.excluded_window_titles)
+    }
+
+    pub fn primary_selection(&self) -> PrimarySelection {
+        PrimarySelection {
+            capture: self.capture_primary_selection,
+            debounce: Duration::from_millis(self.primary_selection_debounce_ms),
+            restore_target: self.restore_target,
+        }
+    }
+
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_items: Some(self.max_history_size),
+            max_age_days: self.max_history_age_days,
+            max_total_bytes: self.max_history_bytes,
+            vacuum: self.vacuum_after_prune,
+        }
+    }
+}
+
+/// 貼り付けの方法。Type は貼り付けを受け付けないアプリケーション向けにテキストを入力する
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteMethod {
+    #[default]
+    CtrlV,
+    ShiftInsert,
+    // 端末エミュレータ向け
+    CtrlShiftV,
+    Type,
+}
+
+/// 履歴のアイテムをコピーするときの書き込み先。PRIMARY は Linux のみ
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestoreTarget {
+    #[default]
+    Clipboard,
+    Primary,
+    Both,
+}
+
+impl RestoreTarget {
+    /// キー操作での貼り付けは CLIPBOARD から行われるため、PRIMARY のみの場合は両方に書き込む
+    pub fn for_paste(self) -> Self {
+        match self {
+            RestoreTarget::Primary => RestoreTarget::Both,
+            target => target,
+        }
+    }
+}
+
+/// グローバルショートカットで実行できる操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutAction {
+    OpenPopup,
+    ToggleMonitoring,
+    PastePrevious,
+    OpenMainWindow,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 4] = [
+        ShortcutAction::OpenPopup,
+        ShortcutAction::ToggleMonitoring,
+        ShortcutAction::PastePrevious,
+        ShortcutAction::OpenMainWindow,
+    ];
+}
+
+/// 操作ごとのショートカット（"Alt+Z" などの形式）。None の場合は割り当てなし
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShortcutSettings {
+    pub open_popup: Option<String>,
+    pub toggle_monitoring: Option<String>,
+    pub paste_previous: Option<String>,
+    pub open_main_window: Option<String>,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            open_popup: Some("Alt+Z".to_string()),
+            toggle_monitoring: None,
+            paste_previous: None,
+            open_main_window: None,
+        }
+    }
+}
+
+impl ShortcutSettings {
+    pub fn get(&self, action: ShortcutAction) -> Option<&str> {
+        match action {
+            ShortcutAction::OpenPopup => self.open_popup.as_deref(),
+            ShortcutAction::ToggleMonitoring => self.toggle_monitoring.as_deref(),
+            ShortcutAction::PastePrevious => self.paste_previous.as_deref(),
+            ShortcutAction::OpenMainWindow => self.open_main_window.as_deref(),
+        }
+    }
+
+    pub fn set(&mut self, action: ShortcutAction, accelerator: Option<String>) {
+        let slot = match action {
+            ShortcutAction::OpenPopup => &mut self.open_popup,
+            ShortcutAction::ToggleMonitoring => &mut self.toggle_monitoring,
+            ShortcutAction::PastePrevious => &mut self.paste_previous,
+            ShortcutAction::OpenMainWindow => &mut self.open_main_window,
+        };
+        *slot = accelerator;
+    }
+
+    /// 割り当てのある操作とショートカットの一覧
+    pub fn iter(&self) -> impl Iterator<Item = (ShortcutAction, &str)> + '_ {
+        ShortcutAction::ALL
+            .into_iter()
+            .filter_map(|action| self.get(action).map(|accelerator| (action, accelerator)))
+    }
+}
+
+/// 設定の読み込みと保存
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// 設定ファイルを読み込む。ファイルがない・壊れている場合はデフォルト値を使う
+    pub fn load(path: &Path) -> Self {
+        let settings = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse settings, using defaults: {e}");
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// 設定を検証して保存する
+    pub fn update(&self, settings: Settings) -> Result<Settings, String> {
+        settings.validate()?;
+        self.save(&settings)?;
+        *self.settings.lock().unwrap() = settings.clone();
+        Ok(settings)
+    }
+
+    /// 現在の設定の一部を変更して保存する
+    pub fn modify(&self, f: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut settings = self.get();
+        f(&mut settings);
+        self.update(settings)
+    }
+
+    // 書き込み途中で終了してもファイルが壊れないよう、一時ファイルに書いてから置き換える
+    fn save(&self, settings: &Settings) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, json).map_err(|e| e.to_string())?;
+        std::fs::rename(&temp_path, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_update_and_reload() {
+        let dir = std::env::temp_dir().join(format!("clipedia-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+
+        // ファイルがない場合はデフォルト値
+        let store = SettingsStore::load(&path);
+        assert_eq!(store.get(), Settings::default());
+
+        let updated = store
+            .modify(|s| {
+                s.start_minimized = true;
+                s.max_history_size = 500;
+            })
+            .unwrap();
+        assert!(updated.start_minimized);
+
+        // 不正な値は保存されない
+        assert!(store.modify(|s| s.max_history_size = 0).is_err());
+        assert!(store
+            .modify(|s| s.excluded_window_titles = vec!["[".to_string()])
+            .is_err());
+        assert_eq!(store.get().max_history_size, 500);
+
+        let reloaded = SettingsStore::load(&path);
+        assert_eq!(reloaded.get(), updated);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"closeToTray": false}"#).unwrap();
+        assert!(!settings.close_to_tray);
+        assert_eq!(settings.max_history_size, 1000);
+        assert_eq!(
+            settings.shortcuts.get(ShortcutAction::OpenPopup),
+            Some("Alt+Z")
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::settings::{ShortcutAction, ShortcutSettings};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// 登録状態。他のアプリケーションが同じキーを使っている場合は registered が false になる
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutStatus {
+    pub action: ShortcutAction,
+    pub accelerator: String,
+    pub registered: bool,
+}
+
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator).map_err(|e| format!("invalid shortcut {accelerator:?}: {e}"))
+}
+
+/// ショートカットの形式と、同じキーが複数の操作に割り当てられていないかを確認する
+pub fn validate(shortcuts: &ShortcutSettings) -> Result<(), String> {
+    let mut seen: HashMap<Shortcut, ShortcutAction> = HashMap::new();
+    for (action, accelerator) in shortcuts.iter() {
+        let shortcut = parse(accelerator)?;
+        if let Some(other) = seen.insert(shortcut, action) {
+            return Err(format!(
+                "shortcut {accelerator:?} is assigned to both {other:?} and {action:?}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 登録済みのショートカットをすべて解除してから登録し直し、登録できなかったものを返す。
+/// 起動時に登録できなかったショートカットは get_shortcut_status で確認できる
+pub fn register_all<R: Runtime>(
+    app: &AppHandle<R>,
+    shortcuts: &ShortcutSettings,
+) -> Vec<(ShortcutAction, String)> {
+    let global_shortcut = app.global_shortcut();
+    let _ = global_shortcut.unregister_all();
+
+    let mut failed = Vec::new();
+    for (action, accelerator) in shortcuts.iter() {
+        let registered = parse(accelerator).and_then(|shortcut| {
+            global_shortcut
+                .on_shortcut(shortcut, move |app, _shortcut, event| {
+                    // 貼り付けはキーを離してから行う（押下中の修飾キーがキー操作に混ざらないように）
+                    let trigger = match action {
+                        ShortcutAction::PastePrevious => ShortcutState::Released,
+                        _ => ShortcutState::Pressed,
+                    };
+                    if event.state() == trigger {
+                        run_action(app, action);
+                    }
+                })
+                .map_err(|e| e.to_string())
+        });
+        if let Err(e) = registered {
+            eprintln!("Failed to register shortcut {accelerator}: {e}");
+            failed.push((action, accelerator.to_string()));
+        }
+    }
+    failed
+}
+
+/// ショートカットの設定を変更する。
+/// 登録に失敗した場合（他のアプリケーションと競合している場合など）は元の設定に戻してエラーを返す
+pub fn apply<R: Runtime>(
+    app: &AppHandle<R>,
+    current: &ShortcutSettings,
+    new: &ShortcutSettings,
+) -> Result<(), String> {
+    validate(new)?;
+
+    let failed = register_all(app, new);
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    register_all(app, current);
+    let conflicts = failed
+        .iter()
+        .map(|(action, accelerator)| format!("{accelerator} ({action:?})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(format!(
+        "shortcut is already in use by another application: {conflicts}"
+    ))
+}
+
+pub fn status<R: Runtime>(app: &AppHandle<R>, shortcuts: &ShortcutSettings) -> Vec<ShortcutStatus> {
+    let global_shortcut = app.global_shortcut();
+    shortcuts
+        .iter()
+        .map(|(action, accelerator)| ShortcutStatus {
+            action,
+            accelerator: accelerator.to_string(),
+            registered: parse(accelerator)
+                .map(|shortcut| global_shortcut.is_registered(shortcut))
+                .unwrap_or(false),
+        })
+        .collect()
+}
+
+fn run_action<R: Runtime>(app: &AppHandle<R>, action: ShortcutAction) {
+    match action {
+        ShortcutAction::OpenPopup => {
+            let _ = crate::show_popup_window(app);
+        }
+        ShortcutAction::ToggleMonitoring => {
+            if let Some(state) = app.try_state::<crate::AppState>() {
+                let enabled = !state.monitor.is_monitoring();
+                if let Err(e) = crate::set_monitoring_enabled(app, enabled) {
+                    eprintln!("Failed to save monitoring state: {e}");
+                }
+            }
+        }
+        ShortcutAction::PastePrevious => {
+            // ひとつ前のアイテムをクリップボードに戻して貼り付ける
+            if let Some(state) = app.try_state::<crate::AppState>() {
+                let previous = state
+                    .db
+                    .lock()
+                    .ok()
+                    .and_then(|db| db.get_recent_items(2).ok())
+                    .and_then(|items| items.into_iter().nth(1));
+                let Some(item) = previous else {
+                    return;
+                };
+                let settings = state.settings.get();
+                let target = settings.restore_target.for_paste();
+                if let Err(e) = state.monitor.copy_item_to(&item.id, false, target) {
+                    eprintln!("Failed to copy previous item: {e}");
+                    return;
+                }
+
+                let db = state.db.clone();
+                let method = settings.paste_method;
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(crate::paste::PASTE_DELAY).await;
+                    if let Err(e) = crate::paste::paste_item(&db, &item.id, method) {
+                        eprintln!("Failed to paste previous item: {e}");
+                    }
+                });
+            }
+        }
+        ShortcutAction::OpenMainWindow => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::fmt::Write;
+use uuid::Uuid;
+
+// スニペット本文のプレースホルダー展開。
+// {{date}} / {{date:%Y-%m-%d}}、{{clipboard}}、{{uuid}}、{{input:名前}} を置き換え、
+// それ以外の {{...}} はコードなどの一部とみなしてそのまま残す
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 展開時に使う値
+pub struct ExpandContext<'a> {
+    pub now: DateTime<Local>,
+    // 展開時点のクリップボードのテキスト
+    pub clipboard: Option<&'a str>,
+    // {{input:名前}} に入力された値
+    pub inputs: &'a HashMap<String, String>,
+}
+
+enum Placeholder<'a> {
+    Date(&'a str),
+    Clipboard,
+    Uuid,
+    Input(&'a str),
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(Placeholder<'a>),
+}
+
+fn parse_placeholder(body: &str) -> Option<Placeholder<'_>> {
+    let (name, arg) = match body.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg)),
+        None => (body.trim(), None),
+    };
+    match (name, arg) {
+        ("date", None) => Some(Placeholder::Date(DEFAULT_DATE_FORMAT)),
+        ("date", Some(format)) if !format.is_empty() => Some(Placeholder::Date(format)),
+        ("clipboard", None) => Some(Placeholder::Clipboard),
+        ("uuid", None) => Some(Placeholder::Uuid),
+        ("input", Some(name)) if !name.trim().is_empty() => Some(Placeholder::Input(name.trim())),
+        _ => None,
+    }
+}
+
+fn segments(template: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        match parse_placeholder(&rest[start + 2..start + 2 + len]) {
+            Some(placeholder) => {
+                segments.push(Segment::Text(&rest[..start]));
+                segments.push(Segment::Placeholder(placeholder));
+            }
+            None => segments.push(Segment::Text(&rest[..end])),
+        }
+        rest = &rest[end..];
+    }
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+/// 展開前に入力してもらう必要がある {{input:名前}} の名前（出現順、重複なし）
+pub fn input_names(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in segments(template) {
+        if let Segment::Placeholder(Placeholder::Input(name)) = segment {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// プレースホルダーを展開する。入力されていない {{input:名前}} や不正な日付書式があればエラーを返す
+pub fn expand(template: &str, context: &ExpandContext) -> Result<String, String> {
+    let missing: Vec<String> = input_names(template)
+        .into_iter()
+        .filter(|name| !context.inputs.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing snippet input: {}", missing.join(", ")));
+    }
+
+    let mut expanded = String::with_capacity(template.len());
+    for segment in segments(template) {
+        match segment {
+            Segment::Text(text) => expanded.push_str(text),
+            Segment::Placeholder(Placeholder::Date(format)) => {
+                format_date(&mut expanded, &context.now, format)?
+            }
+            Segment::Placeholder(Placeholder::Clipboard) => {
+                expanded.push_str(context.clipboard.unwrap_or_default())
+            }
+            Segment::Placeholder(Placeholder::Uuid) => {
+                expanded.push_str(&Uuid::new_v4().to_string())
+            }
+            Segment::Placeholder(Placeholder::Input(name)) => {
+                expanded.push_str(&context.inputs[name])
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+// 不正な書式で chrono が panic しないよう、書式を先に検証する
+fn format_date(out: &mut String, now: &DateTime<Local>, format: &str) -> Result<(), String> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("invalid date format: {format}"));
+    }
+    write!(out, "{}", now.format_with_items(items.into_iter()))
+        .map_err(|_| format!("invalid date format: {format}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn expand_with(template: &str, inputs: &[(&str, &str)]) -> Result<String, String> {
+        let inputs = inputs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let context = ExpandContext {
+            now: Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 0).unwrap(),
+            clipboard: Some("copied"),
+            inputs: &inputs,
+        };
+        expand(template, &context)
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        assert_eq!(
+            expand_with("{{date}} / {{date:%H:%M}} / {{ clipboard }}", &[]).unwrap(),
+            "2024-03-09 / 14:05 / copied"
+        );
+        assert_eq!(
+            expand_with("Dear {{input:Name}}, {{input:Name}}!", &[("Name", "Ann")]).unwrap(),
+            "Dear Ann, Ann!"
+        );
+
+        let uuid = expand_with("{{uuid}}", &[]).unwrap();
+        assert!(Uuid::parse_str(&uuid).is_ok());
+
+        // 未知のプレースホルダーや閉じていない括弧はそのまま残す
+        assert_eq!(
+            expand_with("{{user.name}} {{input:}} {{date", &[]).unwrap(),
+            "{{user.name}} {{input:}} {{date"
+        );
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        assert_eq!(
+            input_names("{{input:To}} {{input:Subject}} {{input:To}}"),
+            vec!["To", "Subject"]
+        );
+        assert_eq!(
+            expand_with("{{input:To}} {{input:Subject}}", &[("To", "x")]).unwrap_err(),
+            "missing snippet input: Subject"
+        );
+        assert!(expand_with("{{date:%Q}}", &[]).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::models::ItemOrder;
+use crate::settings::ShortcutAction;
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
@@ -60,9 +62,20 @@ fn create_tray_menu_with_items<R: Runtime>(
 ) -> tauri::Result<Menu<R>> {
     let menu = Menu::new(app)?;
 
+    // 設定されたショートカットをアクセラレータとして表示する
+    let shortcuts = app
+        .try_state::<crate::AppState>()
+        .map(|state| state.settings.get().shortcuts)
+        .unwrap_or_default();
+
     // クイックアクセス
-    let quick_access =
-        MenuItem::with_id(app, "quick_access", "クイックアクセス", true, Some("Alt+Z"))?;
+    let quick_access = MenuItem::with_id(
+        app,
+        "quick_access",
+        "クイックアクセス",
+        true,
+        shortcuts.get(ShortcutAction::OpenPopup),
+    )?;
     menu.append(&quick_access)?;
 
     // メインウィンドウを開く
@@ -71,7 +84,7 @@ fn create_tray_menu_with_items<R: Runtime>(
         "show_window",
         "メインウィンドウを開く",
         true,
-        None::<&str>,
+        shortcuts.get(ShortcutAction::OpenMainWindow),
     )?;
     menu.append(&show_window)?;
 
@@ -97,11 +110,12 @@ fn create_tray_menu_with_items<R: Runtime>(
             MenuItem::with_id(app, "recent_label", "最近のアイテム:", false, None::<&str>)?;
         menu.append(&recent_label)?;
 
-        for (id, content) in recent_items.iter().take(5) {
-            let truncated = if content.len() > 50 {
-                format!("{}...", &content[..50])
+        for (id, label) in recent_items.iter().take(5) {
+            // マルチバイト文字の途中で切らないように文字数で切り詰める
+            let truncated = if label.chars().count() > 50 {
+                format!("{}...", label.chars().take(50).collect::<String>())
             } else {
-                content.clone()
+                label.clone()
             };
 
             let item =
@@ -129,7 +143,7 @@ fn create_tray_menu_with_items<R: Runtime>(
         "toggle_monitoring",
         monitoring_label,
         true,
-        None::<&str>,
+        shortcuts.get(ShortcutAction::ToggleMonitoring),
     )?;
     menu.append(&toggle_monitoring)?;
 
@@ -163,30 +177,11 @@ fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, event_id: String) {
             }
         }
         "toggle_monitoring" => {
-            // 監視の一時停止/再開
+            // 監視の一時停止/再開（設定にも保存し、トレイメニューを更新する）
             if let Some(state) = app.try_state::<crate::AppState>() {
-                let _is_enabled = state.monitor.toggle_monitoring();
-                // トレイメニューを再作成して更新
-                if let Some(tray) = app.tray_by_id("main") {
-                    // 最近のアイテムを取得
-                    let items = if let Ok(db) = state.db.lock() {
-                        if let Ok(all_items) = db.get_all_items() {
-                            all_items
-                                .into_iter()
-                                .take(5)
-                                .map(|item| (item.id, item.content))
-                                .collect::<Vec<_>>()
-                        } else {
-                            vec![]
-                        }
-                    } else {
-                        vec![]
-                    };
-
-                    // 新しいメニューを作成して設定
-                    if let Ok(new_menu) = create_tray_menu_with_items(app, &items) {
-                        let _ = tray.set_menu(Some(new_menu));
-                    }
+                let enabled = !state.monitor.is_monitoring();
+                if let Err(e) = crate::set_monitoring_enabled(app, enabled) {
+                    eprintln!("Failed to save monitoring state: {e}");
                 }
             }
         }
@@ -199,12 +194,7 @@ fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, event_id: String) {
             if let Some(item_id) = id.strip_prefix("recent_") {
                 // クリップボードにコピー
                 if let Some(state) = app.try_state::<crate::AppState>() {
-                    let db = state.db.lock().unwrap();
-                    if let Ok(items) = db.get_all_items() {
-                        if let Some(item) = items.iter().find(|i| i.id == item_id) {
-                            let _ = state.monitor.copy_to_clipboard(&item.content);
-                        }
-                    }
+                    let _ = state.monitor.copy_item_to_clipboard(item_id, false);
                 }
             }
         }
@@ -214,10 +204,26 @@ fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, event_id: String) {
     }
 }
 
+// データベースから最近のアイテムを読み直してトレイメニューを更新する関数
+pub fn refresh_menu<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+    let items = app
+        .try_state::<crate::AppState>()
+        .and_then(|state| {
+            let db = state.db.lock().ok()?;
+            db.get_items_paginated(0, 5, ItemOrder::Newest).ok()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| (item.id.clone(), item.display_label()))
+        .collect();
+
+    update_recent_items_menu(app, items)
+}
+
 // 最近のアイテムメニューを更新する関数
 pub fn update_recent_items_menu<R: Runtime>(
     app: &tauri::AppHandle<R>,
-    items: Vec<(String, String)>, // (id, content)
+    items: Vec<(String, String)>, // (id, label)
 ) -> tauri::Result<()> {
     // トレイアイコンを取得
     if let Some(tray) = app.tray_by_id("main") {
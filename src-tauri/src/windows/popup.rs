@@ -12,6 +12,11 @@ pub fn create_popup_window<R: Runtime>(
         println!("Found existing popup window, closing it");
         let _ = window.close();
         std::thread::sleep(std::time::Duration::from_millis(100));
+    } else if let Some(state) = app.try_state::<crate::AppState>() {
+        // 貼り付け時にフォーカスを戻すため、直前のウィンドウを記録しておく
+        if let Ok(mut previous_window) = state.previous_window.lock() {
+            *previous_window = crate::focus::active_window();
+        }
     }
 
     println!("Creating new popup window");
@@ -4,7 +4,7 @@ import { ContentArea } from './components/ContentArea';
 import { Settings } from './components/Settings';
 import { UpdateChecker } from './components/UpdateChecker';
 import { VirtualizedClipboardHistory } from './components/VirtualizedClipboardHistory';
-import { ClipboardItem } from './types/clipboard';
+import { ClipboardItem, HISTORY_EVENTS } from './types/clipboard';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 
@@ -33,16 +33,12 @@ function App() {
   useEffect(() => {
     loadClipboardHistory();
     
-    // Refresh every 2 seconds
-    const interval = setInterval(async () => {
-      await loadClipboardHistory();
-      // トレイメニューも更新
-      try {
-        await invoke('update_tray_menu');
-      } catch (error) {
-        console.error('Failed to update tray menu:', error);
-      }
-    }, 2000);
+    // 履歴が変更されたら読み直す（トレイメニューはバックエンドで更新される）
+    const unlistenHistory = HISTORY_EVENTS.map(event =>
+      listen(event, () => {
+        loadClipboardHistory();
+      })
+    );
     
     // Listen for switch-to-settings event from tray menu
     const unlisten = listen('switch-to-settings', () => {
@@ -60,7 +56,7 @@ function App() {
     handleHashChange(); // Check initial hash
     
     return () => {
-      clearInterval(interval);
+      unlistenHistory.forEach(unlistenEvent => unlistenEvent.then(fn => fn()));
       unlisten.then(fn => fn());
       window.removeEventListener('hashchange', handleHashChange);
     };
@@ -68,7 +64,7 @@ function App() {
 
   const handleCopy = async (item: ClipboardItem) => {
     try {
-      await invoke('copy_to_clipboard', { content: item.content });
+      await invoke('copy_to_clipboard', { content: item.content, itemId: item.id });
     } catch (error) {
       console.error('Failed to copy:', error);
     }
@@ -0,0 +1,120 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ExportFilter, ExportFormat, ImportOptions, ImportReport } from '../types/backup';
+import { errorMessage } from '../types/error';
+
+export const BackupManager: React.FC = () => {
+  const [path, setPath] = useState('');
+  const [format, setFormat] = useState<ExportFormat>('json');
+  const [pinnedOnly, setPinnedOnly] = useState(false);
+  const [includeSensitive, setIncludeSensitive] = useState(false);
+  const [mode, setMode] = useState<NonNullable<ImportOptions['mode']>>('merge');
+  const [message, setMessage] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  const handleExport = async () => {
+    if (!path.trim()) return;
+    setLoading(true);
+    try {
+      const filter: ExportFilter = { pinnedOnly, includeSensitive };
+      await invoke('export_history', { path: path.trim(), format, filter });
+      setMessage('エクスポートしました');
+    } catch (error) {
+      console.error('Failed to export history:', error);
+      setMessage(`エクスポートに失敗しました: ${errorMessage(error)}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleImport = async () => {
+    if (!path.trim()) return;
+    if (mode === 'replace' && !confirm('現在の履歴とカスタムタグを削除して読み込みます。よろしいですか？')) {
+      return;
+    }
+    setLoading(true);
+    try {
+      const options: ImportOptions = { mode };
+      const report = await invoke<ImportReport>('import_history', { path: path.trim(), options });
+      setMessage(
+        `${report.imported}件を読み込みました（重複 ${report.duplicates}件、ID の衝突 ${report.conflicts}件、スキップ ${report.skipped}件）`
+      );
+    } catch (error) {
+      console.error('Failed to import history:', error);
+      setMessage(`読み込みに失敗しました: ${errorMessage(error)}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <label className="block p-3">
+        <div className="font-medium">ファイルのパス</div>
+        <div className="text-sm text-gray-500 mb-2">例: /home/user/clipedia-backup.json</div>
+        <input
+          type="text"
+          value={path}
+          onChange={(e) => setPath(e.target.value)}
+          className="w-full px-3 py-2 text-sm font-mono rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+        />
+      </label>
+
+      <div className="flex items-center gap-3 p-3">
+        <select
+          value={format}
+          onChange={(e) => setFormat(e.target.value as ExportFormat)}
+          className="px-3 py-2 text-sm rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+        >
+          <option value="json">JSON（すべて）</option>
+          <option value="csv">CSV（テキストのみ）</option>
+        </select>
+        <label className="flex items-center gap-2 text-sm">
+          <input
+            type="checkbox"
+            checked={pinnedOnly}
+            onChange={(e) => setPinnedOnly(e.target.checked)}
+            className="h-4 w-4 rounded border-gray-300 text-blue-600 focus:ring-blue-500"
+          />
+          ピン留めのみ
+        </label>
+        <label className="flex items-center gap-2 text-sm">
+          <input
+            type="checkbox"
+            checked={includeSensitive}
+            onChange={(e) => setIncludeSensitive(e.target.checked)}
+            className="h-4 w-4 rounded border-gray-300 text-blue-600 focus:ring-blue-500"
+          />
+          機密情報を含める（平文で保存されます）
+        </label>
+        <button
+          onClick={handleExport}
+          disabled={loading || !path.trim()}
+          className="ml-auto px-4 py-2 text-sm bg-blue-600 text-white rounded-lg hover:bg-blue-700 disabled:opacity-50"
+        >
+          エクスポート
+        </button>
+      </div>
+
+      <div className="flex items-center gap-3 p-3">
+        <select
+          value={mode}
+          onChange={(e) => setMode(e.target.value as NonNullable<ImportOptions['mode']>)}
+          className="px-3 py-2 text-sm rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+        >
+          <option value="merge">既存の履歴に追加</option>
+          <option value="replace">既存の履歴を置き換え</option>
+        </select>
+        <button
+          onClick={handleImport}
+          disabled={loading || !path.trim()}
+          className="ml-auto px-4 py-2 text-sm bg-gray-200 dark:bg-gray-700 rounded-lg hover:bg-gray-300 dark:hover:bg-gray-600 disabled:opacity-50"
+        >
+          インポート（JSON）
+        </button>
+      </div>
+
+      {message && <p className="px-3 text-sm text-gray-500">{message}</p>}
+    </div>
+  );
+};
@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { ClipboardItem } from '../types/clipboard';
+import { ClipboardItem, SearchMatch } from '../types/clipboard';
+import { isClipediaError } from '../types/error';
 import { ContentItem } from './ContentItem';
 import { SearchBar } from './SearchBar';
 import { TagFilter } from './TagFilter';
@@ -28,6 +29,9 @@ export const ContentArea: React.FC<ContentAreaProps> = ({
   const [useRegex, setUseRegex] = useState(false);
   const [selectedTags, setSelectedTags] = useState<string[]>([]);
   const [displayItems, setDisplayItems] = useState<ClipboardItem[]>(items);
+  const [searchError, setSearchError] = useState<string | null>(null);
+  // 検索結果の一致箇所（アイテム ID ごと）
+  const [highlights, setHighlights] = useState<Map<string, [number, number][]>>(new Map());
 
   const filters: { id: FilterType; label: string }[] = [
     { id: 'all', label: 'ALL' },
@@ -41,25 +45,24 @@ export const ContentArea: React.FC<ContentAreaProps> = ({
   }, [searchQuery, useRegex, selectedTags, activeFilter, items]);
 
   const performSearch = async () => {
+    setSearchError(null);
     try {
       let filteredItems = items;
+      let matchHighlights = new Map<string, [number, number][]>();
 
-      // 検索クエリがある場合
+      // 検索クエリがある場合は全文検索（関連度順）。正規表現の場合も同じコマンドで検索する
       if (searchQuery) {
-        if (useRegex) {
-          // 正規表現検索
-          const searchResults = await invoke<ClipboardItem[]>('search_items', {
-            pattern: searchQuery,
-            useRegex: true
-          });
-          filteredItems = searchResults;
-        } else {
-          // 通常検索
-          filteredItems = items.filter(item => 
-            item.content.toLowerCase().includes(searchQuery.toLowerCase())
-          );
-        }
+        const matches = await invoke<SearchMatch[]>('search_items_ranked', {
+          query: searchQuery,
+          useRegex,
+        });
+        filteredItems = matches.map(match => ({
+          ...match.item,
+          timestamp: new Date(match.item.timestamp),
+        }));
+        matchHighlights = new Map(matches.map(match => [match.item.id, match.highlights]));
       }
+      setHighlights(matchHighlights);
 
       // タグフィルター
       if (selectedTags.length > 0) {
@@ -80,6 +83,13 @@ export const ContentArea: React.FC<ContentAreaProps> = ({
 
       setDisplayItems(filteredItems);
     } catch (error) {
+      // 入力途中の正規表現は結果を空にしてエラーを表示する
+      if (isClipediaError(error) && error.code === 'invalidRegex') {
+        setSearchError(error.details ?? error.message);
+        setHighlights(new Map());
+        setDisplayItems([]);
+        return;
+      }
       console.error('Search error:', error);
       setDisplayItems(items);
     }
@@ -110,6 +120,9 @@ export const ContentArea: React.FC<ContentAreaProps> = ({
               onSearchModeChange={setUseRegex}
               placeholder="コレクションを検索..."
             />
+            {searchError && (
+              <p className="mt-2 text-xs text-red-500 font-mono">正規表現が正しくありません: {searchError}</p>
+            )}
           </div>
           
           <div className="mb-6">
@@ -152,6 +165,7 @@ export const ContentArea: React.FC<ContentAreaProps> = ({
               <ContentItem
                 key={item.id}
                 item={item}
+                highlights={highlights.get(item.id)}
                 onCopy={onCopy}
                 onPin={onPin}
                 onDelete={onDelete}
@@ -1,18 +1,41 @@
-import React from 'react';
-import { Copy, Pin, PinOff, Trash2 } from 'lucide-react';
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { BookmarkPlus, Copy, Pin, PinOff, Trash2 } from 'lucide-react';
 import { ClipboardItem as ClipboardItemType } from '../types/clipboard';
 import { cn } from '../lib/utils';
 
 interface ContentItemProps {
   item: ClipboardItemType;
+  // 検索で一致した箇所（文字単位の [開始, 終了)）
+  highlights?: [number, number][];
   onCopy: (item: ClipboardItemType) => void;
   onPin: (id: string) => void;
   onDelete: (id: string) => void;
   onAddTag: (id: string, tag: string) => void;
 }
 
+// 一致箇所を <mark> で囲む。位置はバックエンドと同じく Unicode の文字単位
+const renderHighlighted = (content: string, highlights: [number, number][]) => {
+  const chars = Array.from(content);
+  const parts: React.ReactNode[] = [];
+  let position = 0;
+  highlights.forEach(([start, end], index) => {
+    if (start < position) return;
+    parts.push(chars.slice(position, start).join(''));
+    parts.push(
+      <mark key={index} className="bg-yellow-200 dark:bg-yellow-700 rounded-sm">
+        {chars.slice(start, end).join('')}
+      </mark>
+    );
+    position = end;
+  });
+  parts.push(chars.slice(position).join(''));
+  return parts;
+};
+
 export const ContentItem: React.FC<ContentItemProps> = ({
   item,
+  highlights,
   onCopy,
   onPin,
   onDelete,
@@ -34,7 +57,25 @@ export const ContentItem: React.FC<ContentItemProps> = ({
     return 'TEXT';
   };
 
-  const contentType = getContentType(item.content);
+  const contentType = item.type === 'image' ? 'IMAGE' : getContentType(item.content);
+
+  const [thumbnail, setThumbnail] = useState<string | null>(null);
+
+  // テキストアイテムをスニペットとして保存する（名前は本文の最初の行）
+  const handleSaveSnippet = async () => {
+    try {
+      await invoke('promote_item_to_snippet', { itemId: item.id });
+    } catch (error) {
+      console.error('Failed to save snippet:', error);
+    }
+  };
+
+  useEffect(() => {
+    if (item.type !== 'image') return;
+    invoke<string | null>('get_image_thumbnail', { id: item.id })
+      .then(setThumbnail)
+      .catch(error => console.error('Failed to load thumbnail:', error));
+  }, [item.id, item.type]);
 
   return (
     <div className="group border-b border-gray-100 dark:border-gray-900 pb-6 last:border-0">
@@ -43,6 +84,16 @@ export const ContentItem: React.FC<ContentItemProps> = ({
           <div className="text-sm text-gray-400 italic">
             {formatDate(item.timestamp)}
           </div>
+          {item.selection === 'primary' && (
+            <span className="text-xs text-gray-400" title="マウスで選択したテキスト">
+              選択
+            </span>
+          )}
+          {item.copyCount > 1 && (
+            <span className="text-xs text-gray-400" title="コピーした回数">
+              ×{item.copyCount}
+            </span>
+          )}
           {item.isPinned && (
             <Pin className="h-4 w-4 text-yellow-600 dark:text-yellow-400" />
           )}
@@ -58,6 +109,15 @@ export const ContentItem: React.FC<ContentItemProps> = ({
           >
             <Copy className="h-4 w-4" />
           </button>
+          {item.type === 'text' && (
+            <button
+              onClick={handleSaveSnippet}
+              className="text-gray-400 hover:text-gray-600 dark:hover:text-gray-200 transition-colors"
+              title="Save as snippet"
+            >
+              <BookmarkPlus className="h-4 w-4" />
+            </button>
+          )}
           <button
             onClick={() => onPin(item.id)}
             className={cn(
@@ -84,7 +144,13 @@ export const ContentItem: React.FC<ContentItemProps> = ({
         "font-mono text-sm leading-relaxed",
         contentType === 'CODE' ? "bg-gray-50 dark:bg-gray-900 p-4 rounded" : ""
       )}>
-        {item.content}
+        {thumbnail ? (
+          <img src={thumbnail} alt={item.content} className="max-h-40 rounded" />
+        ) : highlights && highlights.length > 0 ? (
+          renderHighlighted(item.content, highlights)
+        ) : (
+          item.content
+        )}
       </div>
 
       {item.tags && item.tags.length > 0 && (
@@ -2,7 +2,8 @@ import React, { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { ClipboardItem } from '../types/clipboard';
-import { Search, Copy, X } from 'lucide-react';
+import { Snippet } from '../types/snippet';
+import { Search, Copy, X, FileText } from 'lucide-react';
 import { cn } from '../lib/utils';
 
 export const PopupApp: React.FC = () => {
@@ -10,6 +11,10 @@ export const PopupApp: React.FC = () => {
   const [searchQuery, setSearchQuery] = useState('');
   const [selectedIndex, setSelectedIndex] = useState(0);
   const [isReady, setIsReady] = useState(false);
+  const [snippets, setSnippets] = useState<Snippet[]>([]);
+  // {{input:名前}} を含むスニペットを選択した場合の入力欄
+  const [pendingSnippet, setPendingSnippet] = useState<{ snippet: Snippet; names: string[] } | null>(null);
+  const [inputValues, setInputValues] = useState<Record<string, string>>({});
   const searchInputRef = useRef<HTMLInputElement>(null);
   const appWindow = getCurrentWindow();
 
@@ -17,6 +22,18 @@ export const PopupApp: React.FC = () => {
   const filteredItems = items.filter(item =>
     item.content.toLowerCase().includes(searchQuery.toLowerCase())
   );
+  // 検索中はスニペットを履歴より先に表示する
+  const entryCount = snippets.length + filteredItems.length;
+
+  useEffect(() => {
+    if (!searchQuery.trim()) {
+      setSnippets([]);
+      return;
+    }
+    invoke<Snippet[]>('search_snippets', { query: searchQuery.trim(), limit: 5 })
+      .then(setSnippets)
+      .catch(error => console.error('Failed to search snippets:', error));
+  }, [searchQuery]);
 
   useEffect(() => {
     // デバッグ用ログ
@@ -42,18 +59,29 @@ export const PopupApp: React.FC = () => {
   // キーボードイベントハンドラー
   useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
+      if (pendingSnippet) {
+        if (e.key === 'Escape') {
+          e.preventDefault();
+          setPendingSnippet(null);
+          searchInputRef.current?.focus();
+        }
+        return;
+      }
+
       if (e.key === 'Escape') {
         appWindow.close();
       } else if (e.key === 'ArrowDown') {
         e.preventDefault();
-        setSelectedIndex(prev => Math.min(prev + 1, filteredItems.length - 1));
+        setSelectedIndex(prev => Math.min(prev + 1, entryCount - 1));
       } else if (e.key === 'ArrowUp') {
         e.preventDefault();
         setSelectedIndex(prev => Math.max(prev - 1, 0));
-      } else if (e.key === 'Enter' && filteredItems.length > 0) {
+      } else if (e.key === 'Enter' && entryCount > 0) {
         e.preventDefault();
-        if (filteredItems[selectedIndex]) {
-          handleCopy(filteredItems[selectedIndex]);
+        if (selectedIndex < snippets.length) {
+          handleSnippet(snippets[selectedIndex]);
+        } else if (filteredItems[selectedIndex - snippets.length]) {
+          handleCopy(filteredItems[selectedIndex - snippets.length]);
         }
       }
     };
@@ -62,7 +90,7 @@ export const PopupApp: React.FC = () => {
     return () => {
       window.removeEventListener('keydown', handleKeyDown);
     };
-  }, [filteredItems, selectedIndex]);
+  }, [filteredItems, snippets, selectedIndex, pendingSnippet]);
 
   // ウィンドウ外クリックで閉じる処理は削除（Rust側でフォーカス管理）
 
@@ -81,13 +109,46 @@ export const PopupApp: React.FC = () => {
 
   const handleCopy = async (item: ClipboardItem) => {
     try {
-      await invoke('copy_to_clipboard', { content: item.content });
-      await appWindow.close();
+      // コピーして直前のアプリケーションに貼り付ける（ポップアップは Rust 側で閉じる）
+      await invoke('paste_item', { itemId: item.id });
     } catch (error) {
       console.error('Failed to copy:', error);
     }
   };
 
+  // プレースホルダーは Rust 側で展開してからコピーする
+  const copySnippet = async (snippet: Snippet, inputs: Record<string, string>) => {
+    try {
+      await invoke('copy_snippet', { id: snippet.id, inputs });
+      appWindow.close();
+    } catch (error) {
+      console.error('Failed to copy snippet:', error);
+    }
+  };
+
+  const handleSnippet = async (snippet: Snippet) => {
+    try {
+      const names = await invoke<string[]>('get_snippet_inputs', { id: snippet.id });
+      if (names.length === 0) {
+        await copySnippet(snippet, {});
+        return;
+      }
+      setInputValues(Object.fromEntries(names.map(name => [name, ''])));
+      setPendingSnippet({ snippet, names });
+    } catch (error) {
+      console.error('Failed to load snippet inputs:', error);
+    }
+  };
+
+  // ファイルアイテムはファイル名の一覧を表示する
+  const formatContent = (item: ClipboardItem) => {
+    if (item.type === 'file' && item.files && item.files.length > 0) {
+      const names = item.files.map(file => file.path.split(/[\\/]/).pop() || file.path);
+      return `📄 ${names.join(', ')}`;
+    }
+    return item.content;
+  };
+
   const formatTime = (date: Date) => {
     return new Intl.DateTimeFormat('ja-JP', {
       hour: '2-digit',
@@ -129,6 +190,56 @@ export const PopupApp: React.FC = () => {
         </div>
       </div>
 
+      {/* スニペットの入力欄 */}
+      {pendingSnippet && (
+        <form
+          className="p-3 border-b border-gray-200 dark:border-gray-700 space-y-2"
+          onSubmit={(e) => {
+            e.preventDefault();
+            copySnippet(pendingSnippet.snippet, inputValues);
+          }}
+        >
+          <p className="text-xs text-gray-500">{pendingSnippet.snippet.name}</p>
+          {pendingSnippet.names.map((name, index) => (
+            <input
+              key={name}
+              autoFocus={index === 0}
+              type="text"
+              value={inputValues[name] ?? ''}
+              onChange={(e) => setInputValues(prev => ({ ...prev, [name]: e.target.value }))}
+              placeholder={name}
+              className="w-full px-3 py-1.5 bg-gray-50 dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded text-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+            />
+          ))}
+          <button type="submit" className="hidden" />
+        </form>
+      )}
+
+      {/* スニペット */}
+      {!pendingSnippet && snippets.length > 0 && (
+        <div className="border-b border-gray-200 dark:border-gray-700">
+          {snippets.map((snippet, index) => (
+            <div
+              key={snippet.id}
+              onClick={() => handleSnippet(snippet)}
+              onMouseEnter={() => setSelectedIndex(index)}
+              className={cn(
+                "px-4 py-2 cursor-pointer transition-colors flex items-center gap-2",
+                index === selectedIndex && "bg-blue-50 dark:bg-blue-900/30"
+              )}
+            >
+              <FileText className="h-4 w-4 text-gray-400 flex-shrink-0" />
+              <p className="flex-1 min-w-0 text-sm text-gray-900 dark:text-gray-100 truncate">
+                {snippet.name}
+              </p>
+              {snippet.abbreviation && (
+                <span className="text-xs text-gray-400">{snippet.abbreviation}</span>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+
       {/* アイテムリスト */}
       <div className="overflow-y-auto max-h-96">
         {filteredItems.length === 0 ? (
@@ -140,16 +251,16 @@ export const PopupApp: React.FC = () => {
             <div
               key={item.id}
               onClick={() => handleCopy(item)}
-              onMouseEnter={() => setSelectedIndex(index)}
+              onMouseEnter={() => setSelectedIndex(snippets.length + index)}
               className={cn(
                 "px-4 py-3 border-b border-gray-100 dark:border-gray-800 cursor-pointer transition-colors",
-                index === selectedIndex && "bg-blue-50 dark:bg-blue-900/30"
+                snippets.length + index === selectedIndex && "bg-blue-50 dark:bg-blue-900/30"
               )}
             >
               <div className="flex items-start justify-between gap-2">
                 <div className="flex-1 min-w-0">
                   <p className="text-sm text-gray-900 dark:text-gray-100 truncate">
-                    {item.content}
+                    {formatContent(item)}
                   </p>
                   <p className="text-xs text-gray-400 mt-1">
                     {formatTime(item.timestamp)}
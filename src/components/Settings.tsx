@@ -1,7 +1,13 @@
-import React, { useState } from 'react';
-import { Settings as SettingsIcon, Monitor, Bell, Keyboard, Download, RefreshCw, Hash } from 'lucide-react';
+import React, { useState, useEffect } from 'react';
+import { Settings as SettingsIcon, Monitor, Bell, Keyboard, Download, RefreshCw, Hash, ClipboardPaste, ShieldOff, MousePointer, Archive } from 'lucide-react';
 import { check } from '@tauri-apps/plugin-updater';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { TagManager } from './TagManager';
+import { ShortcutManager } from './ShortcutManager';
+import { BackupManager } from './BackupManager';
+import { AppSettings } from '../types/settings';
+import { errorMessage } from '../types/error';
 
 interface SettingsProps {
   // 設定の状態を管理するprops（後で実装）
@@ -9,7 +15,7 @@ interface SettingsProps {
 
 export const Settings: React.FC<SettingsProps> = () => {
   const [checkingUpdate, setCheckingUpdate] = useState(false);
-  const [settings, setSettings] = useState({
+  const [settings, setSettings] = useState<AppSettings>({
     // 起動時の動作
     startMinimized: false,
     startWithSystem: false,
@@ -25,17 +31,60 @@ export const Settings: React.FC<SettingsProps> = () => {
     // クリップボード監視
     monitoringEnabled: true,
     maxHistorySize: 1000,
+    maxHistoryAgeDays: null,
+    maxHistoryBytes: null,
+    vacuumAfterPrune: false,
+    excludedApps: ['KeePassXC', '1Password', 'Bitwarden'],
+    excludedWindowTitles: [],
+    sensitiveTtlMinutes: 60,
+    capturePrimarySelection: false,
+    primarySelectionDebounceMs: 500,
+    restoreTarget: 'clipboard',
+    shortcuts: {
+      openPopup: 'Alt+Z',
+      toggleMonitoring: null,
+      pastePrevious: null,
+      openMainWindow: null,
+    },
+    pasteOnSelect: true,
+    pasteMethod: 'ctrlV',
     
     // 自動更新
     autoUpdate: true,
     checkUpdateOnStartup: true,
   });
 
-  const handleSettingChange = (key: string, value: boolean | number) => {
-    setSettings(prev => ({ ...prev, [key]: value }));
-    // TODO: 設定を保存する処理を実装
+  useEffect(() => {
+    invoke<AppSettings>('get_settings')
+      .then(setSettings)
+      .catch(error => console.error('Failed to load settings:', error));
+
+    // トレイなど他の場所で設定が変更された場合に反映する
+    const unlisten = listen<AppSettings>('settings-changed', (event) => {
+      setSettings(event.payload);
+    });
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const handleSettingChange = async (key: keyof AppSettings, value: boolean | number | string | string[]) => {
+    const previous = settings;
+    const next = { ...settings, [key]: value };
+    setSettings(next);
+    try {
+      setSettings(await invoke<AppSettings>('update_settings', { settings: next }));
+    } catch (error) {
+      console.error('Failed to save settings:', error);
+      setSettings(previous);
+      alert(`設定を保存できませんでした: ${errorMessage(error)}`);
+    }
   };
 
+  const splitLines = (value: string) =>
+    value.split('\n').map(line => line.trim()).filter(line => line.length > 0);
+
   const handleCheckUpdate = async () => {
     setCheckingUpdate(true);
     try {
@@ -175,6 +224,95 @@ export const Settings: React.FC<SettingsProps> = () => {
             </label>
           </section>
 
+          {/* 記録しないアプリケーション */}
+          <section className="bg-white dark:bg-gray-900 rounded-lg p-6 space-y-4">
+            <div className="flex items-center gap-3 mb-4">
+              <ShieldOff className="h-5 w-5 text-gray-400" />
+              <h3 className="text-lg font-medium">記録しないアプリケーション</h3>
+            </div>
+            <p className="text-sm text-gray-500">
+              パスワードマネージャーなど、履歴に残さない印を付けてコピーされた内容は常に記録しません
+            </p>
+
+            <label className="block p-3">
+              <div className="font-medium">アプリケーション名</div>
+              <div className="text-sm text-gray-500 mb-2">1行に1つ（例: KeePassXC）</div>
+              <textarea
+                defaultValue={settings.excludedApps.join('\n')}
+                key={`apps-${settings.excludedApps.join(',')}`}
+                onBlur={(e) => handleSettingChange('excludedApps', splitLines(e.target.value))}
+                rows={3}
+                className="w-full px-3 py-2 text-sm rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+              />
+            </label>
+
+            <label className="block p-3">
+              <div className="font-medium">ウィンドウタイトル（正規表現）</div>
+              <div className="text-sm text-gray-500 mb-2">1行に1つ（例: (?i)ネットバンキング）</div>
+              <textarea
+                defaultValue={settings.excludedWindowTitles.join('\n')}
+                key={`titles-${settings.excludedWindowTitles.join(',')}`}
+                onBlur={(e) => handleSettingChange('excludedWindowTitles', splitLines(e.target.value))}
+                rows={3}
+                className="w-full px-3 py-2 text-sm font-mono rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+              />
+            </label>
+          </section>
+
+          {/* 選択範囲（Linux の PRIMARY セレクション） */}
+          <section className="bg-white dark:bg-gray-900 rounded-lg p-6 space-y-4">
+            <div className="flex items-center gap-3 mb-4">
+              <MousePointer className="h-5 w-5 text-gray-400" />
+              <h3 className="text-lg font-medium">選択範囲（Linux）</h3>
+            </div>
+
+            <label className="flex items-center justify-between p-3 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-800 transition-colors cursor-pointer">
+              <div>
+                <div className="font-medium">マウスで選択したテキストも記録</div>
+                <div className="text-sm text-gray-500">PRIMARY セレクションを記録します。ドラッグ中の選択範囲は記録しません</div>
+              </div>
+              <input
+                type="checkbox"
+                checked={settings.capturePrimarySelection}
+                onChange={(e) => handleSettingChange('capturePrimarySelection', e.target.checked)}
+                className="h-5 w-5 rounded border-gray-300 text-blue-600 focus:ring-blue-500"
+              />
+            </label>
+
+            <label className="flex items-center justify-between p-3 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-800 transition-colors">
+              <div>
+                <div className="font-medium">記録するまでの待ち時間（ミリ秒）</div>
+                <div className="text-sm text-gray-500">選択範囲がこの時間変化しなかった場合に記録</div>
+              </div>
+              <input
+                type="number"
+                min={0}
+                max={10000}
+                step={100}
+                value={settings.primarySelectionDebounceMs}
+                disabled={!settings.capturePrimarySelection}
+                onChange={(e) => handleSettingChange('primarySelectionDebounceMs', Number(e.target.value))}
+                className="w-24 px-3 py-1 text-sm rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+              />
+            </label>
+
+            <label className="flex items-center justify-between p-3 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-800 transition-colors">
+              <div>
+                <div className="font-medium">アイテムのコピー先</div>
+                <div className="text-sm text-gray-500">履歴からコピーしたアイテムを書き込むセレクション</div>
+              </div>
+              <select
+                value={settings.restoreTarget}
+                onChange={(e) => handleSettingChange('restoreTarget', e.target.value)}
+                className="px-3 py-1 text-sm rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+              >
+                <option value="clipboard">クリップボード</option>
+                <option value="primary">選択範囲（PRIMARY）</option>
+                <option value="both">両方</option>
+              </select>
+            </label>
+          </section>
+
           {/* ショートカット */}
           <section className="bg-white dark:bg-gray-900 rounded-lg p-6 space-y-4">
             <div className="flex items-center gap-3 mb-4">
@@ -182,15 +320,45 @@ export const Settings: React.FC<SettingsProps> = () => {
               <h3 className="text-lg font-medium">キーボードショートカット</h3>
             </div>
             
-            <div className="space-y-3">
-              <div className="flex items-center justify-between p-3 rounded-lg bg-gray-50 dark:bg-gray-800">
-                <div>
-                  <div className="font-medium">クイックアクセス</div>
-                  <div className="text-sm text-gray-500">ポップアップウィンドウを表示</div>
-                </div>
-                <kbd className="px-3 py-1 text-sm font-semibold text-gray-800 bg-gray-100 dark:bg-gray-700 dark:text-gray-200 rounded">Alt + Z</kbd>
-              </div>
+            <ShortcutManager shortcuts={settings.shortcuts} />
+          </section>
+
+          {/* 貼り付け */}
+          <section className="bg-white dark:bg-gray-900 rounded-lg p-6 space-y-4">
+            <div className="flex items-center gap-3 mb-4">
+              <ClipboardPaste className="h-5 w-5 text-gray-400" />
+              <h3 className="text-lg font-medium">貼り付け</h3>
             </div>
+
+            <label className="flex items-center justify-between p-3 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-800 transition-colors cursor-pointer">
+              <div>
+                <div className="font-medium">選択したアイテムを自動で貼り付け</div>
+                <div className="text-sm text-gray-500">クイックアクセスで選択したアイテムを直前のアプリケーションに貼り付け</div>
+              </div>
+              <input
+                type="checkbox"
+                checked={settings.pasteOnSelect}
+                onChange={(e) => handleSettingChange('pasteOnSelect', e.target.checked)}
+                className="h-5 w-5 rounded border-gray-300 text-blue-600 focus:ring-blue-500"
+              />
+            </label>
+
+            <label className="flex items-center justify-between p-3 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-800 transition-colors">
+              <div>
+                <div className="font-medium">貼り付けの方法</div>
+                <div className="text-sm text-gray-500">貼り付けを受け付けないアプリケーションでは「文字を入力」を選択</div>
+              </div>
+              <select
+                value={settings.pasteMethod}
+                onChange={(e) => handleSettingChange('pasteMethod', e.target.value)}
+                className="px-3 py-1 text-sm rounded border-gray-300 bg-gray-100 dark:bg-gray-700"
+              >
+                <option value="ctrlV">Ctrl + V</option>
+                <option value="shiftInsert">Shift + Insert</option>
+                <option value="ctrlShiftV">Ctrl + Shift + V（端末）</option>
+                <option value="type">文字を入力</option>
+              </select>
+            </label>
           </section>
 
           {/* タグ管理 */}
@@ -203,6 +371,16 @@ export const Settings: React.FC<SettingsProps> = () => {
             <TagManager />
           </section>
 
+          {/* バックアップ */}
+          <section className="bg-white dark:bg-gray-900 rounded-lg p-6">
+            <div className="flex items-center gap-3 mb-4">
+              <Archive className="h-5 w-5 text-gray-400" />
+              <h3 className="text-lg font-medium">バックアップ</h3>
+            </div>
+
+            <BackupManager />
+          </section>
+
           {/* 自動更新 */}
           <section className="bg-white dark:bg-gray-900 rounded-lg p-6 space-y-4">
             <div className="flex items-center gap-3 mb-4">
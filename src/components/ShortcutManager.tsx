@@ -0,0 +1,85 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { AlertTriangle } from 'lucide-react';
+import { ShortcutAction, ShortcutSettings, ShortcutStatus } from '../types/settings';
+import { errorMessage } from '../types/error';
+
+interface ShortcutManagerProps {
+  shortcuts: ShortcutSettings;
+}
+
+const actions: { action: ShortcutAction; label: string; description: string }[] = [
+  { action: 'openPopup', label: 'クイックアクセス', description: 'ポップアップウィンドウを表示' },
+  { action: 'openMainWindow', label: 'メインウィンドウ', description: 'メインウィンドウを表示' },
+  { action: 'toggleMonitoring', label: '監視の切り替え', description: 'クリップボード監視を一時停止/再開' },
+  { action: 'pastePrevious', label: '前のアイテム', description: 'ひとつ前のアイテムを貼り付け' },
+];
+
+export const ShortcutManager: React.FC<ShortcutManagerProps> = ({ shortcuts }) => {
+  const [drafts, setDrafts] = useState<ShortcutSettings>(shortcuts);
+  const [statuses, setStatuses] = useState<ShortcutStatus[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setDrafts(shortcuts);
+    loadStatus();
+  }, [shortcuts]);
+
+  const loadStatus = async () => {
+    try {
+      setStatuses(await invoke<ShortcutStatus[]>('get_shortcut_status'));
+    } catch (error) {
+      console.error('Failed to load shortcut status:', error);
+    }
+  };
+
+  const handleSave = async (action: ShortcutAction) => {
+    const accelerator = drafts[action]?.trim() ?? '';
+    if (accelerator === (shortcuts[action] ?? '')) return;
+
+    try {
+      if (accelerator) {
+        await invoke('register_shortcut', { action, accelerator });
+      } else {
+        await invoke('unregister_shortcut', { action });
+      }
+      setError(null);
+    } catch (error) {
+      setError(errorMessage(error));
+      setDrafts(shortcuts);
+    }
+    loadStatus();
+  };
+
+  const isConflicting = (action: ShortcutAction) =>
+    statuses.some(status => status.action === action && !status.registered);
+
+  return (
+    <div className="space-y-3">
+      {actions.map(({ action, label, description }) => (
+        <div key={action} className="flex items-center justify-between p-3 rounded-lg bg-gray-50 dark:bg-gray-800">
+          <div>
+            <div className="font-medium">{label}</div>
+            <div className="text-sm text-gray-500">{description}</div>
+            {isConflicting(action) && (
+              <div className="flex items-center gap-1 text-sm text-amber-600">
+                <AlertTriangle className="h-4 w-4" />
+                他のアプリケーションと競合しているため登録できませんでした
+              </div>
+            )}
+          </div>
+          <input
+            type="text"
+            value={drafts[action] ?? ''}
+            placeholder="未設定"
+            onChange={(e) => setDrafts(prev => ({ ...prev, [action]: e.target.value }))}
+            onBlur={() => handleSave(action)}
+            onKeyDown={(e) => e.key === 'Enter' && e.currentTarget.blur()}
+            className="w-40 px-3 py-1 text-sm font-semibold text-gray-800 bg-gray-100 dark:bg-gray-700 dark:text-gray-200 rounded"
+          />
+        </div>
+      ))}
+      {error && <p className="text-sm text-red-600">{error}</p>}
+    </div>
+  );
+};
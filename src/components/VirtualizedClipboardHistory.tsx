@@ -1,9 +1,16 @@
 import React, { useState, useMemo, useCallback, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { FixedSizeList as List } from 'react-window';
 import InfiniteLoader from 'react-window-infinite-loader';
 import AutoSizer from 'react-virtualized-auto-sizer';
-import { ClipboardItem as ClipboardItemType } from '../types/clipboard';
+import {
+  ClipboardItem as ClipboardItemType,
+  DeletedItems,
+  ItemCursor,
+  ItemOrder,
+  ItemPage,
+} from '../types/clipboard';
 import { ContentItem } from './ContentItem';
 import { SearchBar } from './SearchBar';
 import { TagFilter } from './TagFilter';
@@ -22,6 +29,38 @@ type FilterType = 'all' | 'text' | 'code' | 'links';
 const ITEMS_PER_PAGE = 50;
 const ITEM_HEIGHT = 200; // ContentItemの高さの推定値
 
+// タイムスタンプを Date オブジェクトに変換
+const withDate = (item: ClipboardItemType): ClipboardItemType => ({
+  ...item,
+  timestamp: new Date(item.timestamp),
+});
+
+// 変換前のアイテムからカーソルを作る（タイムスタンプはバックエンドの文字列のまま使う）
+const cursorOf = (item: ClipboardItemType): ItemCursor => ({
+  isPinned: item.isPinned,
+  timestamp: item.timestamp as unknown as string,
+  copyCount: item.copyCount,
+  id: item.id,
+});
+
+const isNewer = (item: ClipboardItemType, cursor: ItemCursor) => {
+  const time = new Date(item.timestamp).getTime();
+  const cursorTime = new Date(cursor.timestamp).getTime();
+  return time > cursorTime || (time === cursorTime && item.id > cursor.id);
+};
+
+// 追加されたアイテムを先頭に挿入する（ピン留めされていないものはピン留めされたアイテムの後）
+const prependItems = (prev: ClipboardItemType[], added: ClipboardItemType[]) => {
+  const addedIds = new Set(added.map(item => item.id));
+  const rest = prev.filter(item => !addedIds.has(item.id));
+  return [
+    ...added.filter(item => item.isPinned),
+    ...rest.filter(item => item.isPinned),
+    ...added.filter(item => !item.isPinned),
+    ...rest.filter(item => !item.isPinned),
+  ];
+};
+
 export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
   onCopy,
   onPin,
@@ -36,9 +75,14 @@ export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
   const [useRegex, setUseRegex] = useState(false);
   const [selectedTags, setSelectedTags] = useState<string[]>([]);
   const [isLoading, setIsLoading] = useState(false);
-  const loadedPages = useRef(new Set<number>());
+  const [sortOrder, setSortOrder] = useState<ItemOrder>('newest');
+  // ページの読み込み中に並び順が変わっても最新の値を使う
+  const sortOrderRef = useRef<ItemOrder>('newest');
+  // 次のページの開始位置（null の場合は最後まで読み込み済み）
+  const nextCursor = useRef<ItemCursor | null>(null);
+  // 読み込んだアイテムのうち最も新しいもの。get_items_since の基準にする
+  const latestCursor = useRef<ItemCursor | null>(null);
   const infiniteLoaderRef = useRef<InfiniteLoader>(null);
-  const itemsCache = useRef<Map<number, ClipboardItemType>>(new Map());
 
   const filters: { id: FilterType; label: string }[] = [
     { id: 'all', label: 'ALL' },
@@ -47,35 +91,103 @@ export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
     { id: 'links', label: 'LINKS' },
   ];
 
+  const sortOrders: { id: ItemOrder; label: string }[] = [
+    { id: 'newest', label: '新しい順' },
+    { id: 'oldest', label: '古い順' },
+    { id: 'mostUsed', label: 'よく使う順' },
+  ];
+
   // Load initial data
   useEffect(() => {
     loadInitialData();
   }, []);
 
-  const loadInitialData = async () => {
-    try {
-      const count = await invoke<number>('get_total_count');
-      setTotalCount(count);
-      
-      // Load first page
-      if (count > 0) {
-        const firstPage = await invoke<ClipboardItemType[]>('get_items_paginated', {
-          offset: 0,
+  // 履歴の変更イベントを受け取ったら表示を更新する
+  useEffect(() => {
+    const unlistenUpdated = listen<ClipboardItemType>('item-updated', event => {
+      const updated = withDate(event.payload);
+      setItems(prevItems => prevItems.map(item => (item.id === updated.id ? updated : item)));
+    });
+
+    // 新しい順の場合は追加されたアイテムだけを取得して先頭に挿入する
+    const unlistenAdded = listen('item-added', async () => {
+      const cursor = latestCursor.current;
+      if (sortOrderRef.current !== 'newest' || !cursor) {
+        loadInitialData();
+        return;
+      }
+
+      try {
+        const added = await invoke<ClipboardItemType[]>('get_items_since', {
+          cursor,
           limit: ITEMS_PER_PAGE,
         });
-        
-        // タイムスタンプを Date オブジェクトに変換
-        const itemsWithDates = firstPage.map(item => ({
-          ...item,
-          timestamp: new Date(item.timestamp)
-        }));
-        
-        setItems(itemsWithDates);
-        itemsWithDates.forEach((item, idx) => {
-          itemsCache.current.set(idx, item);
-        });
-        loadedPages.current.add(0);
+        // 取得しきれないほど追加された場合は読み直す
+        if (added.length >= ITEMS_PER_PAGE) {
+          loadInitialData();
+          return;
+        }
+        rememberLatest(added);
+        setItems(prevItems => prependItems(prevItems, added.map(withDate)));
+        setTotalCount(await invoke<number>('get_total_count'));
+      } catch (error) {
+        console.error('Failed to load new items:', error);
+      }
+    });
+
+    const unlistenDeleted = listen<DeletedItems>('item-deleted', async event => {
+      const ids = new Set(event.payload.ids);
+      setItems(prevItems => prevItems.filter(item => !ids.has(item.id)));
+      setTotalCount(await invoke<number>('get_total_count'));
+    });
+
+    const unlistenCleared = listen('history-cleared', () => {
+      loadInitialData();
+    });
+
+    return () => {
+      [unlistenUpdated, unlistenAdded, unlistenDeleted, unlistenCleared].forEach(unlisten =>
+        unlisten.then(fn => fn())
+      );
+    };
+  }, []);
+
+  // 並び順が変わったら読み込み済みのアイテムを破棄して読み直す
+  const handleSortOrderChange = (order: ItemOrder) => {
+    sortOrderRef.current = order;
+    setSortOrder(order);
+    setItems([]);
+    loadInitialData();
+  };
+
+  const rememberLatest = (loaded: ClipboardItemType[]) => {
+    loaded.forEach(item => {
+      if (!latestCursor.current || isNewer(item, latestCursor.current)) {
+        latestCursor.current = cursorOf(item);
       }
+    });
+  };
+
+  // 読み込めなかった履歴の行を知らせる
+  const reportMalformed = (page: ItemPage) => {
+    page.malformed.forEach(row => {
+      console.warn(`Skipped malformed clipboard item ${row.id ?? '(unknown id)'}: ${row.reason}`);
+    });
+  };
+
+  const loadInitialData = async () => {
+    nextCursor.current = null;
+    latestCursor.current = null;
+    try {
+      const page = await invoke<ItemPage>('query_items', {
+        query: { order: sortOrderRef.current, limit: ITEMS_PER_PAGE },
+      });
+
+      nextCursor.current = page.nextCursor;
+      reportMalformed(page);
+      rememberLatest(page.items);
+      setTotalCount(page.totalCount);
+      setItems(page.items.map(withDate));
     } catch (error) {
       console.error('Failed to load initial data:', error);
     }
@@ -127,52 +239,33 @@ export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
 
   // Check if more items need to be loaded
   const isItemLoaded = useCallback((index: number) => {
-    return itemsCache.current.has(index);
-  }, []);
+    return index < items.length;
+  }, [items.length]);
 
-  // Load more items
-  const loadMoreItems = useCallback(async (startIndex: number, stopIndex: number) => {
-    if (isLoading) return;
-    
-    setIsLoading(true);
-    const startPage = Math.floor(startIndex / ITEMS_PER_PAGE);
-    const endPage = Math.floor(stopIndex / ITEMS_PER_PAGE);
+  // 前のページの最後のアイテムの次から読み込む（読み込み中にアイテムが追加されても位置がずれない）
+  const loadMoreItems = useCallback(async () => {
+    const cursor = nextCursor.current;
+    if (isLoading || !cursor) return;
 
+    setIsLoading(true);
     try {
-      const promises = [];
-      for (let page = startPage; page <= endPage; page++) {
-        if (!loadedPages.current.has(page)) {
-          const offset = page * ITEMS_PER_PAGE;
-          promises.push(
-            invoke<ClipboardItemType[]>('get_items_paginated', {
-              offset,
-              limit: ITEMS_PER_PAGE,
-            }).then(newItems => ({ page, items: newItems }))
-          );
-        }
-      }
+      const page = await invoke<ItemPage>('query_items', {
+        query: { order: sortOrderRef.current, cursor, limit: ITEMS_PER_PAGE },
+      });
+      // 読み込み中に一覧が読み直された場合は破棄する
+      if (nextCursor.current !== cursor) return;
 
-      const results = await Promise.all(promises);
-      
-      if (results.length > 0) {
-        setItems(prevItems => {
-          const newItems = [...prevItems];
-          results.forEach(({ page, items: pageItems }) => {
-            const startIdx = page * ITEMS_PER_PAGE;
-            pageItems.forEach((item, idx) => {
-              const itemWithDate = {
-                ...item,
-                timestamp: new Date(item.timestamp)
-              };
-              const globalIdx = startIdx + idx;
-              newItems[globalIdx] = itemWithDate;
-              itemsCache.current.set(globalIdx, itemWithDate);
-            });
-            loadedPages.current.add(page);
-          });
-          return newItems;
-        });
-      }
+      nextCursor.current = page.nextCursor;
+      reportMalformed(page);
+      rememberLatest(page.items);
+      setTotalCount(page.totalCount);
+      setItems(prevItems => {
+        const loadedIds = new Set(prevItems.map(item => item.id));
+        return [
+          ...prevItems,
+          ...page.items.filter(item => !loadedIds.has(item.id)).map(withDate),
+        ];
+      });
     } catch (error) {
       console.error('Failed to load more items:', error);
     } finally {
@@ -203,18 +296,11 @@ export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
         item.id === id ? { ...item, isPinned: !item.isPinned } : item
       )
     );
-    // Update cache
-    itemsCache.current.forEach((item, key) => {
-      if (item.id === id) {
-        itemsCache.current.set(key, { ...item, isPinned: !item.isPinned });
-      }
-    });
   }, [onPin]);
 
   const handleDelete = useCallback(async (id: string) => {
+    // 一覧からは item-deleted イベントで取り除く
     await onDelete(id);
-    // Reset and reload data
-    await loadInitialData();
   }, [onDelete]);
 
   // Row renderer
@@ -265,7 +351,7 @@ export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
             />
           </div>
           
-          <div className="flex gap-2">
+          <div className="flex items-center gap-2">
             {filters.map((filter) => (
               <button
                 key={filter.id}
@@ -280,6 +366,17 @@ export const VirtualizedClipboardHistory: React.FC<ClipboardHistoryProps> = ({
                 {filter.label}
               </button>
             ))}
+            <select
+              value={sortOrder}
+              onChange={(e) => handleSortOrderChange(e.target.value as ItemOrder)}
+              className="ml-auto px-4 py-2 text-sm bg-white dark:bg-gray-900 text-gray-600 dark:text-gray-400 border border-gray-200 dark:border-gray-800"
+            >
+              {sortOrders.map((order) => (
+                <option key={order.id} value={order.id}>
+                  {order.label}
+                </option>
+              ))}
+            </select>
           </div>
         </div>
       </div>
@@ -0,0 +1,27 @@
+export type ExportFormat = 'json' | 'csv';
+
+// エクスポートするアイテムの条件（すべて省略可能）
+export interface ExportFilter {
+  since?: string;
+  until?: string;
+  tag?: string;
+  pinnedOnly?: boolean;
+  includeImages?: boolean;
+  // 機密情報（平文で書き出される）を含める
+  includeSensitive?: boolean;
+}
+
+export interface ImportOptions {
+  // merge: 既存の履歴に追加 / replace: 既存の履歴を削除してから読み込む
+  mode?: 'merge' | 'replace';
+  // 同じ ID の別の内容が保存済みの場合の扱い
+  onConflict?: 'keepBoth' | 'skip' | 'replace';
+}
+
+export interface ImportReport {
+  imported: number;
+  duplicates: number;
+  conflicts: number;
+  skipped: number;
+  tags: number;
+}
@@ -6,11 +6,138 @@ export interface ClipboardItem {
   isPinned: boolean;
   tags?: string[];
   applicationSource?: string;
+  image?: ImageInfo;
+  files?: FileEntry[];
+  // 同じ内容がコピーされた回数（履歴からのコピーを含む）
+  copyCount: number;
+  lastUsedAt?: string;
+  // primary は Linux でマウスで選択しただけのテキスト
+  selection: 'clipboard' | 'primary';
+}
+
+// 履歴の並び順（ピン留めされたアイテムは常に先頭）
+export type ItemOrder = 'newest' | 'oldest' | 'mostUsed';
+
+export interface FileEntry {
+  path: string;
+  exists: boolean;
+  isDir: boolean;
+  size?: number;
+  mimeType?: string;
+}
+
+export interface ImageInfo {
+  width: number;
+  height: number;
+  byteSize: number;
 }
 
 export interface ClipboardHistoryState {
   items: ClipboardItem[];
   searchQuery: string;
   selectedTags: string[];
-  sortOrder: 'newest' | 'oldest' | 'mostUsed';
-}
\ No newline at end of file
+  sortOrder: ItemOrder;
+}
+
+export interface SearchMatch {
+  item: ClipboardItem;
+  rank: number;
+  snippet: string;
+  // [start, end) の文字位置
+  snippetHighlights: [number, number][];
+  highlights: [number, number][];
+}
+
+// query_items の条件（すべて省略可能。指定した条件をすべて満たすアイテムを返す）
+export interface ItemFilter {
+  // 内容に含まれる文字列（useRegex が true の場合は正規表現）
+  text?: string;
+  useRegex?: boolean;
+  // いずれかのタグが付いている
+  anyTags?: string[];
+  // すべてのタグが付いている
+  allTags?: string[];
+  excludeTags?: string[];
+  itemTypes?: ClipboardItem['type'][];
+  pinnedOnly?: boolean;
+  source?: string;
+  since?: string;
+  until?: string;
+  // 内容の文字数
+  minLength?: number;
+  maxLength?: number;
+  // 機密情報のアイテムも内容の条件の対象にする
+  includeSensitive?: boolean;
+}
+
+// 次のページの開始位置（前のページの nextCursor をそのまま渡す）
+export interface ItemCursor {
+  isPinned: boolean;
+  timestamp: string;
+  copyCount: number;
+  id: string;
+}
+
+export interface ItemQuery {
+  filter?: ItemFilter;
+  order?: ItemOrder;
+  cursor?: ItemCursor;
+  limit?: number;
+}
+
+export interface ItemPage {
+  items: ClipboardItem[];
+  // カーソルに関係なく条件に一致するアイテムの数
+  totalCount: number;
+  // 続きがない場合は null
+  nextCursor: ItemCursor | null;
+  // このページの範囲で読み込めずに飛ばした行
+  malformed: MalformedRow[];
+}
+
+export interface MalformedRow {
+  // ID も読めなかった場合は null
+  id: string | null;
+  reason: string;
+}
+
+export interface FormatData {
+  format: 'html' | 'rtf';
+  data: string;
+}
+
+// transform_item で適用できる変換（配列の順に適用される）
+export type Transform =
+  | 'trim'
+  | 'uppercase'
+  | 'lowercase'
+  | 'titleCase'
+  | 'snakeCase'
+  | 'camelCase'
+  | 'kebabCase'
+  | 'jsonPretty'
+  | 'jsonMinify'
+  | 'urlEncode'
+  | 'urlDecode'
+  | 'base64Encode'
+  | 'base64Decode'
+  | 'htmlEscape'
+  | 'sortLines'
+  | 'dedupeLines'
+  | 'stripAnsi';
+
+export interface TransformResult {
+  content: string;
+  // 新しい履歴アイテムとして保存した場合のアイテム ID
+  savedItemId?: string;
+}
+
+// バックエンドから送信される履歴の変更イベント
+export const HISTORY_EVENTS = ['item-added', 'item-updated', 'item-deleted', 'history-cleared'] as const;
+
+export type HistoryEventName = (typeof HISTORY_EVENTS)[number];
+
+// item-deleted イベントの内容
+export interface DeletedItems {
+  ids: string[];
+}
@@ -0,0 +1,28 @@
+// コマンドが失敗したときに Rust 側から返されるエラー
+export type ClipediaErrorCode =
+  | 'notFound'
+  | 'databaseLocked'
+  | 'wrongPassphrase'
+  | 'encryption'
+  | 'invalidRegex'
+  | 'clipboardUnavailable'
+  | 'invalidInput'
+  | 'conflict'
+  | 'database'
+  | 'io'
+  | 'internal';
+
+export interface ClipediaError {
+  code: ClipediaErrorCode;
+  message: string;
+  details?: string | null;
+}
+
+export function isClipediaError(error: unknown): error is ClipediaError {
+  return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+}
+
+// 画面に表示するメッセージ
+export function errorMessage(error: unknown): string {
+  return isClipediaError(error) ? error.message : String(error);
+}
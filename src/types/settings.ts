@@ -0,0 +1,59 @@
+export type ShortcutAction = 'openPopup' | 'toggleMonitoring' | 'pastePrevious' | 'openMainWindow';
+
+export type PasteMethod = 'ctrlV' | 'shiftInsert' | 'ctrlShiftV' | 'type';
+
+// 履歴のアイテムをコピーするときの書き込み先（PRIMARY は Linux のみ）
+export type RestoreTarget = 'clipboard' | 'primary' | 'both';
+
+export interface ShortcutSettings {
+  openPopup: string | null;
+  toggleMonitoring: string | null;
+  pastePrevious: string | null;
+  openMainWindow: string | null;
+}
+
+export interface ShortcutStatus {
+  action: ShortcutAction;
+  accelerator: string;
+  registered: boolean;
+}
+
+export interface AppSettings {
+  // 起動時の動作
+  startMinimized: boolean;
+  startWithSystem: boolean;
+  rememberLastState: boolean;
+
+  // トレイの動作
+  minimizeToTray: boolean;
+  closeToTray: boolean;
+
+  // 通知設定
+  showNotifications: boolean;
+
+  // クリップボード監視
+  monitoringEnabled: boolean;
+  maxHistorySize: number;
+  maxHistoryAgeDays: number | null;
+  maxHistoryBytes: number | null;
+  vacuumAfterPrune: boolean;
+  excludedApps: string[];
+  excludedWindowTitles: string[];
+  sensitiveTtlMinutes: number;
+
+  // Linux の PRIMARY セレクション
+  capturePrimarySelection: boolean;
+  primarySelectionDebounceMs: number;
+  restoreTarget: RestoreTarget;
+
+  // グローバルショートカット
+  shortcuts: ShortcutSettings;
+
+  // 貼り付け
+  pasteOnSelect: boolean;
+  pasteMethod: PasteMethod;
+
+  // 自動更新
+  autoUpdate: boolean;
+  checkUpdateOnStartup: boolean;
+}
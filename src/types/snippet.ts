@@ -0,0 +1,10 @@
+export interface Snippet {
+  id: string;
+  name: string;
+  // 検索欄に入力して呼び出すための短い名前
+  abbreviation?: string;
+  // {{date:%Y-%m-%d}}、{{clipboard}}、{{uuid}}、{{input:名前}} はコピー時に展開される
+  content: string;
+  createdAt: string;
+  updatedAt: string;
+}